@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use crate::maze::Maze;
+use crate::path_finders::path_finder_interface::{PathFinder, SolverEvent};
+use crate::path_finders::stats::SolverStats;
+
+// How often the worker sends the steps it took, so a front-end can show progress while it runs.
+const SEND_INTERVAL: Duration = Duration::from_millis(15);
+
+/// One `iterate` call made by the worker: its events, and the cells left to expand and the
+/// solver's statistics after it.
+pub type BackgroundStep = (Vec<SolverEvent>, usize, SolverStats);
+
+/// A solver running to the end on a worker thread, so a large maze does not hold up drawing.
+///
+/// The worker searches its own copy of the maze and streams the steps back, the front-end
+/// applies them at its own pace with `next_step`. Dropping it cancels the search.
+pub struct BackgroundSolve {
+    // Only touched from one thread at a time, the lock is there to make the receiver `Sync`.
+    // Steps are sent in batches every `SEND_INTERVAL`.
+    receiver: Mutex<Receiver<Vec<BackgroundStep>>>,
+    pending: VecDeque<BackgroundStep>,
+    cancel: Arc<AtomicBool>,
+    worker: Option<JoinHandle<Box<dyn PathFinder + Sync + Send>>>,
+}
+
+impl BackgroundSolve {
+    /// Runs `solver` on `maze` until it finds the end, runs out of cells or is cancelled.
+    pub fn start(mut solver: Box<dyn PathFinder + Sync + Send>, maze: Maze) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cancelled = cancel.clone();
+        let worker = thread::spawn(move || {
+            let mut steps = vec![];
+            let mut last_sent = Instant::now();
+            while !cancelled.load(Ordering::Relaxed) && !solver.is_solved() && !solver.is_exhausted() {
+                let events = solver.iterate(&maze);
+                steps.push((events, solver.frontier_size(), solver.get_stats().clone()));
+                if last_sent.elapsed() >= SEND_INTERVAL {
+                    if sender.send(std::mem::take(&mut steps)).is_err() {
+                        break;
+                    }
+                    last_sent = Instant::now();
+                }
+            }
+            // Nobody may be listening any more, then there is nothing to do about it.
+            let _ = sender.send(steps);
+            solver
+        });
+        Self {
+            receiver: Mutex::new(receiver),
+            pending: VecDeque::new(),
+            cancel,
+            worker: Some(worker),
+        }
+    }
+
+    fn receive(&mut self) {
+        let receiver = self.receiver.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner());
+        while let Ok(steps) = receiver.try_recv() {
+            self.pending.extend(steps);
+        }
+    }
+
+    /// The oldest step not handed out yet, `None` if the worker has not sent a newer one.
+    pub fn next_step(&mut self) -> Option<BackgroundStep> {
+        if self.pending.is_empty() {
+            self.receive();
+        }
+        self.pending.pop_front()
+    }
+
+    /// Asks the worker to stop after its current step. The steps it already took still come through.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Whether the worker stopped and every step it took was handed out.
+    pub fn is_finished(&mut self) -> bool {
+        if !self.worker.as_ref().is_some_and(|worker| worker.is_finished()) {
+            return false;
+        }
+        // The worker sent everything before it stopped, what is left is already in the channel.
+        self.receive();
+        self.pending.is_empty()
+    }
+
+    /// The solver as the worker left it. Only call it once `is_finished` is true.
+    pub fn finish(mut self) -> Box<dyn PathFinder + Sync + Send> {
+        let worker = self.worker.take().expect("the worker is only taken here");
+        worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+impl Drop for BackgroundSolve {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{generate_maze, Generator};
+    use crate::path_finders::SOLVERS;
+    use crate::path_finders::dfs::DFS;
+    use crate::path_finders::solve;
+
+    // Every step the worker took, waiting for it to stop.
+    fn drain(background: &mut BackgroundSolve) -> Vec<BackgroundStep> {
+        let mut steps = vec![];
+        while !background.is_finished() {
+            match background.next_step() {
+                Some(step) => steps.push(step),
+                None => thread::yield_now(),
+            }
+        }
+        steps
+    }
+
+    // Timings differ between runs, everything else should not.
+    fn without_timing(steps: Vec<BackgroundStep>) -> Vec<BackgroundStep> {
+        steps.into_iter().map(|(events, frontier_size, stats)| (events, frontier_size, SolverStats { elapsed: Duration::ZERO, ..stats })).collect()
+    }
+
+    #[test]
+    fn matches_a_foreground_run() {
+        let maze = generate_maze(40, 30, Generator::Scatter, 4);
+        for entry in SOLVERS {
+            let mut foreground = (entry.create)(&maze);
+            let expected = without_timing(solve(foreground.as_mut(), &maze));
+            let mut background = BackgroundSolve::start((entry.create)(&maze), maze.clone());
+            assert_eq!(without_timing(drain(&mut background)), expected, "{}", entry.name);
+            let solver = background.finish();
+            assert_eq!(solver.get_path(&maze), foreground.get_path(&maze), "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn cancel_stops_early_and_leaves_a_consistent_solver() {
+        let maze = generate_maze(300, 300, Generator::Scatter, 5);
+        let mut background = BackgroundSolve::start(Box::new(DFS::new(&maze)), maze.clone());
+        background.cancel();
+        assert!(background.is_cancelled());
+        let steps = drain(&mut background);
+        let mut solver = background.finish();
+        assert!(!solver.is_solved());
+        assert_eq!(solver.get_stats().iterations, steps.len());
+        // It picks up where the worker stopped and ends on the path an uninterrupted run finds.
+        solve(solver.as_mut(), &maze);
+        let mut uninterrupted = DFS::new(&maze);
+        solve(&mut uninterrupted, &maze);
+        assert_eq!(solver.get_path(&maze), uninterrupted.get_path(&maze));
+    }
+
+    #[test]
+    fn dropping_cancels() {
+        let maze = generate_maze(300, 300, Generator::Scatter, 5);
+        let background = BackgroundSolve::start(Box::new(DFS::new(&maze)), maze);
+        let cancel = background.cancel.clone();
+        drop(background);
+        assert!(cancel.load(Ordering::Relaxed));
+    }
+}
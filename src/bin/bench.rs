@@ -0,0 +1,186 @@
+use std::process;
+use mazes::cli;
+use mazes::maze::{self, Generator, Maze};
+use mazes::path_finders::all_solvers;
+use mazes::path_finders::path_finder_interface::PathFinder;
+use mazes::path_finders::stats::SolverStats;
+
+const USAGE: &str = "Usage: bench [--mazes N] [--size WIDTHxHEIGHT] [--generator NAME] [--seed SEED] [--format table|csv|json]";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+struct Options {
+    mazes: usize,
+    size: (usize, usize),
+    generator: Generator,
+    seed: u64,
+    format: Format,
+}
+
+struct Run {
+    maze: usize,
+    seed: u64,
+    algorithm: String,
+    solved: bool,
+    stats: SolverStats,
+}
+
+impl Run {
+    fn path_length(&self) -> usize {
+        self.stats.path_length.unwrap_or(0)
+    }
+
+    fn path_cost(&self) -> usize {
+        self.stats.path_cost.unwrap_or(0)
+    }
+
+    fn accuracy(&self) -> f32 {
+        self.stats.accuracy().unwrap_or(0.)
+    }
+
+    fn time_ms(&self) -> f64 {
+        self.stats.elapsed.as_secs_f64() * 1000.
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        mazes: 20,
+        size: (50, 50),
+        generator: Generator::Backtracker,
+        seed: rand::random(),
+        format: Format::Table,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{} expects a value", arg))?;
+        match arg.as_str() {
+            "--mazes" => options.mazes = value.parse().map_err(|_| format!("invalid maze count `{}`", value))?,
+            "--size" => options.size = cli::parse_size(arg, value)?,
+            "--generator" => options.generator = cli::parse_generator(value)?,
+            "--seed" => options.seed = cli::parse_seed(value)?,
+            "--format" => {
+                options.format = match value.as_str() {
+                    "table" => Format::Table,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    _ => return Err(format!("unknown format `{}`", value)),
+                }
+            }
+            _ => return Err(format!("unknown option `{}`", arg)),
+        }
+    }
+    Ok(options)
+}
+
+// Runs `solver` until it either finds the end or runs out of cells to expand.
+fn run(maze_index: usize, maze: &Maze, mut solver: Box<dyn PathFinder + Sync + Send>) -> Run {
+    // Solvers only read the maze, so every algorithm searches the same one.
+    while !solver.is_solved() && !solver.is_exhausted() {
+        solver.iterate(maze);
+    }
+    Run {
+        maze: maze_index,
+        seed: maze.seed,
+        algorithm: solver.name().to_string(),
+        solved: solver.is_solved(),
+        stats: solver.get_stats().clone(),
+    }
+}
+
+fn print_table(runs: &[Run]) {
+    println!("{:>5} {:>20} {:<10} {:>6} {:>9} {:>9} {:>6} {:>9} {:>8} {:>6} {:>6} {:>9} {:>10} {:>10}",
+        "maze", "seed", "algorithm", "solved", "expanded", "generated", "again", "peak open", "peak KiB",
+        "path", "cost", "accuracy", "iterations", "time (ms)");
+    for run in runs {
+        println!("{:>5} {:>20} {:<10} {:>6} {:>9} {:>9} {:>6} {:>9} {:>8} {:>6} {:>6} {:>9.3} {:>10} {:>10.3}",
+            run.maze, run.seed, run.algorithm, run.solved, run.stats.nodes_expanded, run.stats.nodes_generated,
+            run.stats.re_expansions, run.stats.peak_open_set, run.stats.peak_memory.div_ceil(1024),
+            run.path_length(), run.path_cost(), run.accuracy(), run.stats.iterations, run.time_ms());
+    }
+
+    println!();
+    println!("Averages:");
+    println!("{:<10} {:>6} {:>9} {:>9} {:>6} {:>9} {:>8} {:>6} {:>9} {:>10}",
+        "algorithm", "solved", "expanded", "generated", "again", "peak open", "peak KiB", "path", "accuracy", "time (ms)");
+    let mut algorithms: Vec<&str> = vec![];
+    for run in runs {
+        if !algorithms.contains(&run.algorithm.as_str()) {
+            algorithms.push(&run.algorithm);
+        }
+    }
+    for algorithm in algorithms {
+        let runs = runs.iter().filter(|run| run.algorithm == algorithm).collect::<Vec<&Run>>();
+        let count = runs.len() as f64;
+        let average = |value: &dyn Fn(&Run) -> f64| runs.iter().map(|run| value(run)).sum::<f64>() / count;
+        println!("{:<10} {:>6} {:>9.1} {:>9.1} {:>6.1} {:>9.1} {:>8.1} {:>6.1} {:>9.3} {:>10.3}",
+            algorithm,
+            runs.iter().filter(|run| run.solved).count(),
+            average(&|run| run.stats.nodes_expanded as f64),
+            average(&|run| run.stats.nodes_generated as f64),
+            average(&|run| run.stats.re_expansions as f64),
+            average(&|run| run.stats.peak_open_set as f64),
+            average(&|run| run.stats.peak_memory as f64 / 1024.),
+            average(&|run| run.path_length() as f64),
+            average(&|run| run.accuracy() as f64),
+            average(&|run| run.time_ms()));
+    }
+}
+
+fn print_csv(runs: &[Run]) {
+    println!("maze,seed,algorithm,solved,expanded,generated,re_expanded,peak_open_set,peak_memory_bytes,path_length,path_cost,accuracy,iterations,time_ms");
+    for run in runs {
+        println!("{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            run.maze, run.seed, run.algorithm, run.solved, run.stats.nodes_expanded, run.stats.nodes_generated,
+            run.stats.re_expansions, run.stats.peak_open_set, run.stats.peak_memory, run.path_length(),
+            run.path_cost(), run.accuracy(), run.stats.iterations, run.time_ms());
+    }
+}
+
+fn print_json(runs: &[Run]) {
+    let rows = runs.iter().map(|run| format!(
+        "  {{\"maze\": {}, \"seed\": {}, \"algorithm\": \"{}\", \"solved\": {}, \"expanded\": {}, \"generated\": {}, \"re_expanded\": {}, \"peak_open_set\": {}, \"peak_memory_bytes\": {}, \"path_length\": {}, \"path_cost\": {}, \"accuracy\": {}, \"iterations\": {}, \"time_ms\": {}}}",
+        run.maze, run.seed, run.algorithm.replace('\\', "\\\\").replace('"', "\\\""), run.solved,
+        run.stats.nodes_expanded, run.stats.nodes_generated, run.stats.re_expansions, run.stats.peak_open_set,
+        run.stats.peak_memory, run.path_length(), run.path_cost(), run.accuracy(), run.stats.iterations, run.time_ms(),
+    )).collect::<Vec<String>>();
+    println!("[\n{}\n]", rows.join(",\n"));
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let options = match parse_options(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n{}", err, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let mut runs = vec![];
+    for index in 0..options.mazes {
+        let seed = options.seed.wrapping_add(index as u64);
+        let maze = maze::generate_maze(options.size.0, options.size.1, options.generator, seed);
+        for solver in all_solvers(&maze) {
+            runs.push(run(index, &maze, solver));
+        }
+    }
+
+    match options.format {
+        Format::Table => {
+            println!("{} {}x{} mazes from `{}`, first seed {}", options.mazes, options.size.0, options.size.1, options.generator.name(), options.seed);
+            print_table(&runs);
+        }
+        Format::Csv => print_csv(&runs),
+        Format::Json => print_json(&runs),
+    }
+}
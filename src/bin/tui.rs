@@ -0,0 +1,300 @@
+use std::io::{self, Write};
+use std::process;
+use std::time::{Duration, Instant};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+use mazes::cli::{self, Options};
+use mazes::maze::{self, CellState, Generator, Maze};
+use mazes::path_finders::{new_solver, SOLVERS};
+use mazes::path_finders::path_finder_interface::PathFinder;
+use mazes::path_finders::replay::Replay;
+use mazes::run::SolverRun;
+use mazes::speed::Speed;
+use mazes::trace::Trace;
+
+const TRACE_FILE: &str = "maze.trace";
+// Time between two solver steps, the terminal equivalent of a frame.
+const FRAME: Duration = Duration::from_millis(30);
+const KEYS: &str = "space: start/pause  s/b: step/back  i: instant/stop  +/-: speed  m: per frame/second  r: new maze  1-9: algorithm  arrows: size  t: save trace  q: quit";
+
+struct App {
+    maze: Maze,
+    // The background solve is started with `i` and cancelled by replacing the run.
+    run: SolverRun,
+    play: bool,
+    maze_size: (usize, usize),
+    generator: Generator,
+    // Seed for the next generated maze, random once it has been used.
+    seed: Option<u64>,
+    speed: Speed,
+    // Part of an iteration owed by a per-second speed.
+    carry: f32,
+    // The trace passed with `--replay`; resets restore its maze instead of generating one.
+    trace: Option<Trace>,
+    message: String,
+    quit: bool,
+}
+
+impl App {
+    fn new(mut options: Options, trace: Option<Trace>) -> Self {
+        let (maze, solver): (Maze, Box<dyn PathFinder + Sync + Send>) = match &trace {
+            Some(trace) => (trace.maze.clone(), Box::new(Replay::new(trace))),
+            None => {
+                let maze = generate_maze(options.maze_size, options.generator, &mut options.seed);
+                let solver = new_solver(&options.algorithm, &maze).expect("algorithm names are checked when parsing options");
+                (maze, solver)
+            }
+        };
+        Self {
+            run: SolverRun::new(solver, &maze),
+            maze,
+            play: options.play,
+            maze_size: options.maze_size,
+            generator: options.generator,
+            seed: options.seed,
+            speed: options.speed,
+            carry: 0.,
+            trace,
+            message: String::new(),
+            quit: false,
+        }
+    }
+
+    fn next_maze(&mut self) -> Maze {
+        match &self.trace {
+            Some(trace) => trace.maze.clone(),
+            None => generate_maze(self.maze_size, self.generator, &mut self.seed),
+        }
+    }
+
+    fn reset_maze(&mut self) {
+        self.play = false;
+        self.maze = self.next_maze();
+        self.run = SolverRun::new(self.run.solver.get_new_solver(&self.maze), &self.maze);
+        self.message.clear();
+    }
+
+    fn change_algorithm(&mut self, name: &str) {
+        if let Some(solver) = new_solver(name, &self.maze) {
+            self.maze.reset_explored_paths();
+            self.run = SolverRun::new(solver, &self.maze);
+            self.play = false;
+            self.message = format!("Changing to {}", self.run.solver.name());
+        }
+    }
+
+    fn change_maze_size(&mut self, dx: isize, dy: isize) {
+        let width = self.maze_size.0 as isize + dx;
+        let height = self.maze_size.1 as isize + dy;
+        if width >= 1 && height >= 1 {
+            self.maze_size = (width as usize, height as usize);
+            self.reset_maze();
+        }
+    }
+
+    fn save_trace(&mut self) {
+        let trace = Trace::new(&self.maze, self.run.solver.name(), self.run.history.events());
+        self.message = match trace.save(TRACE_FILE) {
+            Ok(()) => format!("Saved {} steps of {} to {}", trace.steps.len(), trace.algorithm, TRACE_FILE),
+            Err(err) => format!("Could not save trace: {}", err),
+        };
+    }
+
+    // Runs the solver to the end on a worker thread, or stops it if it is already running there.
+    fn solve_in_background(&mut self) {
+        match &self.run.background {
+            Some(background) => background.cancel(),
+            None => self.run.solve_in_background(&mut self.maze),
+        }
+    }
+
+    // Shows what the background solve did since the last frame.
+    fn apply_background(&mut self) {
+        if self.run.apply_background(&mut self.maze, FRAME / 2) {
+            self.message = "Solved!".to_string();
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let busy = self.run.background.is_some();
+        match code {
+            KeyCode::Char(' ') => self.play = !self.play,
+            KeyCode::Char('r') => self.reset_maze(),
+            KeyCode::Char('t') => self.save_trace(),
+            KeyCode::Char('s') if !self.play && !busy => self.advance(1),
+            KeyCode::Char('b') if !self.play && !busy => self.run.step_back(&mut self.maze),
+            KeyCode::Char('i') => self.solve_in_background(),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.speed = self.speed.faster(),
+            KeyCode::Char('-') => self.speed = self.speed.slower(),
+            KeyCode::Char('m') => {
+                self.speed = self.speed.toggle_unit();
+                self.carry = 0.;
+            }
+            KeyCode::Char(digit @ '1'..='9') => {
+                if let Some(entry) = SOLVERS.get(digit as usize - '1' as usize) {
+                    self.change_algorithm(entry.name);
+                }
+            }
+            KeyCode::Up => self.change_maze_size(0, -1),
+            KeyCode::Down => self.change_maze_size(0, 1),
+            KeyCode::Left => self.change_maze_size(-1, 0),
+            KeyCode::Right => self.change_maze_size(1, 0),
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            _ => {}
+        }
+    }
+
+    fn run_solver(&mut self, delta: Duration) {
+        let iterations = self.speed.iterations(delta, &mut self.carry);
+        self.advance(iterations);
+    }
+
+    fn advance(&mut self, iterations: usize) {
+        if self.run.advance(&mut self.maze, iterations) {
+            self.message = "Solved!".to_string();
+        }
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (columns, rows) = terminal::size()?;
+        // Two columns per cell keeps the cells roughly square, three rows stay free for the status.
+        let visible_width = self.maze.width.min(columns as usize / 2);
+        let visible_height = self.maze.height.min((rows as usize).saturating_sub(3));
+        queue!(out, terminal::Clear(terminal::ClearType::All))?;
+        // Row 0 is at the bottom, like in the visualizer window.
+        for (line, y) in (0..self.maze.height).rev().take(visible_height).enumerate() {
+            queue!(out, cursor::MoveTo(0, line as u16))?;
+            for x in 0..visible_width {
+                queue!(out, SetBackgroundColor(get_color(self.maze.get(x, y))), Print("  "))?;
+            }
+            queue!(out, ResetColor)?;
+        }
+        let state = if let Some(background) = &self.run.background {
+            if background.is_cancelled() { "stopping" } else { "solving in the background" }
+        } else if !self.run.history.is_at_end() {
+            if self.play { "running" } else { "rewound" }
+        } else if self.run.solver.is_solved() {
+            "solved"
+        } else if self.play {
+            "running"
+        } else {
+            "paused"
+        };
+        let stats = self.run.stats();
+        let status = format!("{} | {} | {} | step {}/{} | {}x{} | {}", self.run.solver.name(), state, self.speed,
+            self.run.history.cursor(), self.run.history.len(), self.maze.width, self.maze.height, self.message);
+        queue!(
+            out,
+            cursor::MoveTo(0, visible_height as u16),
+            Print(truncate(&status, columns as usize)),
+            cursor::MoveTo(0, visible_height as u16 + 1),
+            Print(truncate(&stats.to_string(), columns as usize)),
+            cursor::MoveTo(0, visible_height as u16 + 2),
+            Print(truncate(KEYS, columns as usize)),
+        )?;
+        out.flush()
+    }
+}
+
+// Uses `seed` for this maze and random seeds after it.
+fn generate_maze(size: (usize, usize), generator: Generator, seed: &mut Option<u64>) -> Maze {
+    let seed = seed.take().unwrap_or_else(rand::random);
+    maze::generate_maze(size.0, size.1, generator, seed)
+}
+
+fn get_color(cell: &CellState) -> Color {
+    match cell {
+        CellState::START => Color::Green,
+        CellState::END => Color::Red,
+        CellState::WALL => Color::Black,
+        CellState::UNEXPLORED => Color::White,
+        CellState::EXPLORED => Color::DarkGrey,
+        CellState::FRONTIER => Color::DarkYellow,
+        CellState::PATH => Color::Blue,
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+// Puts the terminal back the way it was, also when the app panics.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn run(mut app: App) -> io::Result<()> {
+    let _terminal = RawTerminal::enter()?;
+    let mut out = io::stdout();
+    let mut last_step = Instant::now();
+    let mut dirty = true;
+    while !app.quit {
+        if dirty {
+            app.draw(&mut out)?;
+            dirty = false;
+        }
+        if event::poll(FRAME.saturating_sub(last_step.elapsed()))? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    app.handle_key(key.code, key.modifiers);
+                    dirty = true;
+                }
+                Event::Resize(_, _) => dirty = true,
+                _ => {}
+            }
+        }
+        if last_step.elapsed() >= FRAME {
+            let delta = last_step.elapsed();
+            last_step = Instant::now();
+            if app.run.background.is_some() {
+                app.apply_background();
+                dirty = true;
+            } else if app.play && !(app.run.solver.is_solved() && app.run.history.is_at_end()) {
+                app.run_solver(delta);
+                dirty = true;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("Usage: tui [OPTIONS]\n{}", cli::options_help());
+        return;
+    }
+    let options = match cli::parse(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\nUsage: tui [OPTIONS]\n{}", err, cli::options_help());
+            process::exit(2);
+        }
+    };
+    let trace = options.replay.as_deref().map(|path| match Trace::load(path) {
+        Ok(trace) => trace,
+        Err(err) => {
+            eprintln!("Could not load trace {}: {}", path, err);
+            process::exit(2);
+        }
+    });
+    if let Err(err) = run(App::new(options, trace)) {
+        eprintln!("Terminal error: {}", err);
+        process::exit(1);
+    }
+}
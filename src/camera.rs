@@ -0,0 +1,56 @@
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+
+const MIN_SCALE: f32 = 0.05;
+const MAX_SCALE: f32 = 10.;
+// Scale change per line of scrolling.
+const ZOOM_STEP: f32 = 1.15;
+
+// The mouse wheel zooms around the pointer, dragging with the middle button pans.
+pub fn zoom_and_pan(
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+    window_query: Query<&Window>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut motion_events: EventReader<MouseMotion>,
+    mouse_input: Res<ButtonInput<MouseButton>>) {
+
+    let window = window_query.single();
+    let (mut transform, mut projection) = camera_query.single_mut();
+    let lines = wheel_events
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 100.,
+        })
+        .sum::<f32>();
+    if lines != 0. {
+        let scale = (projection.scale * ZOOM_STEP.powf(-lines)).clamp(MIN_SCALE, MAX_SCALE);
+        // Keep the point under the pointer where it is, or the centre when the pointer is outside.
+        let offset = window.cursor_position().map_or(Vec2::ZERO, |cursor| {
+            let from_centre = cursor - Vec2::new(window.width(), window.height()) / 2.;
+            Vec2::new(from_centre.x, -from_centre.y)
+        });
+        let anchor = transform.translation.truncate() + offset * projection.scale;
+        let translation = anchor - offset * scale;
+        transform.translation = translation.extend(transform.translation.z);
+        projection.scale = scale;
+    }
+    let moved = motion_events.read().map(|event| event.delta).sum::<Vec2>();
+    if mouse_input.pressed(MouseButton::Middle) {
+        transform.translation.x -= moved.x * projection.scale;
+        transform.translation.y += moved.y * projection.scale;
+    }
+}
+
+pub fn reset_view(
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>) {
+
+    if !keyboard_input.just_pressed(KeyCode::KeyF) {
+        return;
+    }
+    let (mut transform, mut projection) = camera_query.single_mut();
+    transform.translation.x = 0.;
+    transform.translation.y = 0.;
+    projection.scale = 1.;
+}
@@ -0,0 +1,185 @@
+use crate::maze::Generator;
+use crate::path_finders::{find_solver, solver_names, SOLVERS};
+use crate::speed::Speed;
+
+/// Options shared by the front-ends, printed after their `Usage:` line.
+pub fn options_help() -> String {
+    format!("Options:
+  --size WIDTHxHEIGHT     maze size in cells (default 30x30)
+  --seed SEED             seed of the first maze (default random)
+  --generator NAME        backtracker or scatter (default backtracker)
+  --algorithm NAME        {} (default {})
+  --window WIDTHxHEIGHT   window size in pixels (default 800x800, ignored in the terminal)
+  --renderer NAME         sprites or image (default image above 250000 cells, ignored in the terminal)
+  --speed N|N/s           solver iterations per frame, or per second with /s (default 1)
+  --paused                start with the solver paused
+  --running               start with the solver running (default)
+  --replay FILE           play back a trace saved with T", solver_names().join(", "), SOLVERS[0].name)
+}
+
+/// How the window draws the maze.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Renderer {
+    /// A tile sprite per cell.
+    Sprites,
+    /// A few large textures with a pixel per cell, for mazes too big for a sprite each.
+    Image,
+}
+
+impl Renderer {
+    pub const ALL: [Renderer; 2] = [Renderer::Sprites, Renderer::Image];
+    /// Mazes with more cells than this use the image renderer unless told otherwise.
+    pub const SPRITE_LIMIT: usize = 250_000;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Renderer::Sprites => "sprites",
+            Renderer::Image => "image",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|renderer| renderer.name() == name)
+    }
+
+    /// The renderer for a maze of `cells` cells, `choice` if one was given.
+    pub fn for_maze(choice: Option<Renderer>, cells: usize) -> Self {
+        choice.unwrap_or(if cells > Self::SPRITE_LIMIT { Renderer::Image } else { Renderer::Sprites })
+    }
+}
+
+/// Starting configuration of a front-end, from the command line.
+pub struct Options {
+    pub maze_size: (usize, usize),
+    pub seed: Option<u64>,
+    pub generator: Generator,
+    pub algorithm: String,
+    pub window_size: (usize, usize),
+    /// `None` picks one by maze size.
+    pub renderer: Option<Renderer>,
+    pub speed: Speed,
+    pub play: bool,
+    pub replay: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            maze_size: (30, 30),
+            seed: None,
+            generator: Generator::Backtracker,
+            algorithm: SOLVERS[0].name.to_string(),
+            window_size: (800, 800),
+            renderer: None,
+            speed: Speed::PerFrame(1),
+            play: true,
+            replay: None,
+        }
+    }
+}
+
+/// `WIDTHxHEIGHT` with both sides above 0, the value of `option`.
+pub fn parse_size(option: &str, value: &str) -> Result<(usize, usize), String> {
+    value
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .filter(|&(width, height)| width > 0 && height > 0)
+        .ok_or_else(|| format!("invalid {} `{}`, expected WIDTHxHEIGHT with both above 0", option, value))
+}
+
+pub fn parse_seed(value: &str) -> Result<u64, String> {
+    value.parse().map_err(|_| format!("invalid seed `{}`", value))
+}
+
+pub fn parse_generator(value: &str) -> Result<Generator, String> {
+    Generator::from_name(value).ok_or_else(|| {
+        let names = Generator::ALL.iter().map(|generator| generator.name()).collect::<Vec<&str>>();
+        format!("unknown generator `{}`, expected one of: {}", value, names.join(", "))
+    })
+}
+
+pub fn parse(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--paused" => {
+                options.play = false;
+                continue;
+            }
+            "--running" => {
+                options.play = true;
+                continue;
+            }
+            _ => {}
+        }
+        let value = args.next().ok_or_else(|| format!("{} expects a value", arg))?;
+        match arg.as_str() {
+            "--size" => options.maze_size = parse_size(arg, value)?,
+            "--seed" => options.seed = Some(parse_seed(value)?),
+            "--generator" => options.generator = parse_generator(value)?,
+            "--algorithm" => {
+                if find_solver(value).is_none() {
+                    return Err(format!("unknown algorithm `{}`, expected one of: {}", value, solver_names().join(", ")));
+                }
+                options.algorithm = value.clone();
+            }
+            "--window" => options.window_size = parse_size(arg, value)?,
+            "--renderer" => {
+                options.renderer = Some(Renderer::from_name(value).ok_or_else(|| {
+                    let names = Renderer::ALL.iter().map(|renderer| renderer.name()).collect::<Vec<&str>>();
+                    format!("unknown renderer `{}`, expected one of: {}", value, names.join(", "))
+                })?)
+            }
+            "--speed" => {
+                options.speed = Speed::parse(value)
+                    .ok_or_else(|| format!("invalid speed `{}`, expected a number above 0, optionally followed by /s", value))?
+            }
+            "--replay" => options.replay = Some(value.clone()),
+            _ => return Err(format!("unknown option `{}`", arg)),
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_error;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn parse_line(line: &str) -> Result<Options, String> {
+        parse(&args(line))
+    }
+
+    #[test]
+    fn parses_every_option() {
+        let options = parse(&args("--size 40x20 --seed 9 --generator scatter --algorithm dijkstra --window 640x480 --renderer sprites --speed 5/s --paused --replay run.trace")).unwrap();
+        assert_eq!(options.maze_size, (40, 20));
+        assert_eq!(options.seed, Some(9));
+        assert_eq!(options.generator, Generator::Scatter);
+        assert_eq!(options.algorithm, "dijkstra");
+        assert_eq!(options.window_size, (640, 480));
+        assert_eq!(options.renderer, Some(Renderer::Sprites));
+        assert_eq!(options.speed, Speed::PerSecond(5));
+        assert!(!options.play);
+        assert_eq!(options.replay.as_deref(), Some("run.trace"));
+    }
+
+    #[test]
+    fn invalid_options_are_errors() {
+        assert_eq!(parse_error(parse_line, "--size"), "--size expects a value");
+        assert_eq!(parse_error(parse_line, "--size 0x10"), "invalid --size `0x10`, expected WIDTHxHEIGHT with both above 0");
+        assert_eq!(parse_error(parse_line, "--size 10"), "invalid --size `10`, expected WIDTHxHEIGHT with both above 0");
+        assert_eq!(parse_error(parse_line, "--window 800x-1"), "invalid --window `800x-1`, expected WIDTHxHEIGHT with both above 0");
+        assert_eq!(parse_error(parse_line, "--seed -1"), "invalid seed `-1`");
+        assert_eq!(parse_error(parse_line, "--generator prim"), "unknown generator `prim`, expected one of: backtracker, scatter");
+        assert_eq!(parse_error(parse_line, "--algorithm bfs"), "unknown algorithm `bfs`, expected one of: astar, dijkstra, dfs");
+        assert_eq!(parse_error(parse_line, "--renderer ascii"), "unknown renderer `ascii`, expected one of: sprites, image");
+        assert_eq!(parse_error(parse_line, "--speed 0"), "invalid speed `0`, expected a number above 0, optionally followed by /s");
+        assert_eq!(parse_error(parse_line, "--fast 1"), "unknown option `--fast`");
+    }
+}
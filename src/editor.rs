@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use mazes::maze::{CellState, Maze};
+use mazes::visualizer::{self, CellSize};
+use mazes::plugin::{LoadedTrace, MazeState, Solver};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    PaintWalls,
+    EraseWalls,
+    MoveStart,
+    MoveEnd,
+}
+
+/// What the mouse button held down is doing to the maze.
+#[derive(Resource, Default)]
+pub struct Editor {
+    edit: Option<Edit>,
+}
+
+fn edit_cell(maze: &mut Maze, edit: Edit, (x, y): (usize, usize)) -> bool {
+    let state = maze.get(x, y).clone();
+    let is_marker = (x, y) == maze.start || (x, y) == maze.end;
+    match edit {
+        Edit::PaintWalls if !is_marker && state != CellState::WALL => maze.set(x, y, CellState::WALL),
+        Edit::EraseWalls if state == CellState::WALL => maze.set(x, y, CellState::UNEXPLORED),
+        Edit::MoveStart if !is_marker && state != CellState::WALL => {
+            maze.set(maze.start.0, maze.start.1, CellState::UNEXPLORED);
+            maze.start = (x, y);
+            maze.set(x, y, CellState::START);
+        }
+        Edit::MoveEnd if !is_marker && state != CellState::WALL => {
+            maze.set(maze.end.0, maze.end.1, CellState::UNEXPLORED);
+            maze.end = (x, y);
+            maze.set(x, y, CellState::END);
+        }
+        _ => return false,
+    }
+    true
+}
+
+// Left-drag paints walls or drags the start and end, right-drag erases walls. Every edit restarts the solver,
+// which waits in `Editing` until the button is released.
+#[allow(clippy::too_many_arguments)]
+pub fn edit_maze(
+    mut editor: ResMut<Editor>,
+    mut maze: ResMut<Maze>,
+    mut solver: ResMut<Solver>,
+    state: Res<State<MazeState>>,
+    mut next_state: ResMut<NextState<MazeState>>,
+    window_query: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    ui_query: Query<&Interaction>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    cell_size: Res<CellSize>,
+    loaded_trace: Option<Res<LoadedTrace>>) {
+
+    if mouse_input.just_released(MouseButton::Left) || mouse_input.just_released(MouseButton::Right) {
+        editor.edit = None;
+        if *state.get() == MazeState::Editing {
+            next_state.set(MazeState::Paused);
+        }
+    }
+    // A replay is tied to the maze it was recorded on.
+    if loaded_trace.is_some() {
+        return;
+    }
+    let window = window_query.single();
+    let (camera, camera_transform) = camera_query.single();
+    let Some(hovered) = visualizer::cell_under_cursor(window, camera, camera_transform, &maze, cell_size.0 as f32) else {
+        return;
+    };
+    let over_ui = ui_query.iter().any(|interaction| *interaction != Interaction::None);
+    let inspecting = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if !over_ui && !inspecting {
+        if mouse_input.just_pressed(MouseButton::Left) {
+            editor.edit = Some(if hovered == maze.start {
+                Edit::MoveStart
+            } else if hovered == maze.end {
+                Edit::MoveEnd
+            } else {
+                Edit::PaintWalls
+            });
+            next_state.set(MazeState::Editing);
+        }
+        if mouse_input.just_pressed(MouseButton::Right) {
+            editor.edit = Some(Edit::EraseWalls);
+            next_state.set(MazeState::Editing);
+        }
+    }
+    let Some(edit) = editor.edit else {
+        return;
+    };
+    if !edit_cell(&mut maze, edit, hovered) {
+        return;
+    }
+    maze.reset_explored_paths();
+    *solver = Solver::new(solver.solver.get_new_solver(&maze), &maze);
+}
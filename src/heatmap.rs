@@ -0,0 +1,314 @@
+use bevy::prelude::*;
+use mazes::maze::{CellState, Maze};
+use mazes::history::Step;
+use mazes::path_finders::path_finder_interface::SolverEvent;
+use mazes::visualizer::{self, Cell, CellAssets, MazeView};
+use mazes::plugin::Solver;
+
+const LEGEND_STEPS: usize = 16;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HeatmapMode {
+    Off,
+    /// Cost of the best known route from the start.
+    Cost,
+    /// When the cell was first expanded.
+    Order,
+    /// How often the cell was expanded.
+    Visits,
+}
+
+impl HeatmapMode {
+    fn next(self) -> Self {
+        match self {
+            HeatmapMode::Off => HeatmapMode::Cost,
+            HeatmapMode::Cost => HeatmapMode::Order,
+            HeatmapMode::Order => HeatmapMode::Visits,
+            HeatmapMode::Visits => HeatmapMode::Off,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            HeatmapMode::Off => "off",
+            HeatmapMode::Cost => "g cost",
+            HeatmapMode::Order => "expansion order",
+            HeatmapMode::Visits => "visit count",
+        }
+    }
+}
+
+/// Tints the expanded cells on a gradient instead of drawing them with the explored tile.
+#[derive(Resource)]
+pub struct Heatmap {
+    mode: HeatmapMode,
+    // Value of every cell in row-major order, `None` for cells that were never expanded.
+    values: Vec<Option<usize>>,
+    range: Option<(usize, usize)>,
+    // Expansion counts and first expansions over the steps the heatmap has followed, which are
+    // the first `cursor` steps of the history numbered `run`.
+    visits: Vec<usize>,
+    order: Vec<Option<usize>>,
+    expanded: usize,
+    run: Option<usize>,
+    cursor: usize,
+    // Cells to repaint since the last frame.
+    changed: Vec<usize>,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            mode: HeatmapMode::Off,
+            values: vec![],
+            range: None,
+            visits: vec![],
+            order: vec![],
+            expanded: 0,
+            run: None,
+            cursor: 0,
+            changed: vec![],
+        }
+    }
+}
+
+impl Heatmap {
+    fn reset(&mut self, cells: usize, run: usize) {
+        self.values = vec![None; cells];
+        self.range = None;
+        self.visits = vec![0; cells];
+        self.order = vec![None; cells];
+        self.expanded = 0;
+        self.run = Some(run);
+        self.cursor = 0;
+    }
+
+    // Moves the counts to `target` steps of `steps`, listing the cells the steps touched.
+    fn follow(&mut self, steps: &[Step], target: usize, maze: &Maze) -> Vec<usize> {
+        let mut touched = vec![];
+        while self.cursor > target {
+            self.cursor -= 1;
+            let step = &steps[self.cursor];
+            for index in expansions(step, maze).rev() {
+                self.visits[index] -= 1;
+                self.expanded -= 1;
+                if self.order[index] == Some(self.expanded) {
+                    self.order[index] = None;
+                }
+                touched.push(index);
+            }
+            touched.extend(step.changes.iter().filter_map(|change| maze.cell_id(change.position)));
+        }
+        while self.cursor < target {
+            let step = &steps[self.cursor];
+            for index in expansions(step, maze) {
+                self.visits[index] += 1;
+                self.order[index].get_or_insert(self.expanded);
+                self.expanded += 1;
+                touched.push(index);
+            }
+            touched.extend(step.changes.iter().filter_map(|change| maze.cell_id(change.position)));
+            self.cursor += 1;
+        }
+        touched
+    }
+
+    fn value(&self, index: usize, solver: &Solver, maze: &Maze) -> Option<usize> {
+        self.order[index]?;
+        match self.mode {
+            HeatmapMode::Off => None,
+            HeatmapMode::Cost => maze.position(index).and_then(|position| solver.solver.cell_info(maze, position).g),
+            HeatmapMode::Order => self.order[index],
+            HeatmapMode::Visits => Some(self.visits[index]),
+        }
+    }
+}
+
+// Cells `step` expanded, leaving out the ones a replayed trace put outside the maze.
+fn expansions<'a>(step: &'a Step, maze: &'a Maze) -> impl DoubleEndedIterator<Item = usize> + 'a {
+    step.events.iter().filter_map(|event| match event {
+        SolverEvent::Expanded(position) => maze.cell_id(*position),
+        _ => None,
+    })
+}
+
+#[derive(Component)]
+pub struct HeatmapLegend;
+
+#[derive(Component)]
+pub struct HeatmapLegendLabel;
+
+// Dark blue through teal to yellow, `t` runs from 0 to 1.
+fn gradient(t: f32) -> Color {
+    let stops = [(0.12, 0.16, 0.55), (0.1, 0.65, 0.6), (1., 0.88, 0.2)];
+    let scaled = t.clamp(0., 1.) * (stops.len() - 1) as f32;
+    let index = (scaled as usize).min(stops.len() - 2);
+    let local = scaled - index as f32;
+    let (from, to) = (stops[index], stops[index + 1]);
+    Color::rgb(
+        from.0 + (to.0 - from.0) * local,
+        from.1 + (to.1 - from.1) * local,
+        from.2 + (to.2 - from.2) * local,
+    )
+}
+
+pub fn setup_heatmap_legend(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(30.),
+                    right: Val::Px(8.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(8.)),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.75).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            HeatmapLegend,
+        ))
+        .with_children(|legend| {
+            legend.spawn((
+                TextBundle::from_section("", TextStyle { font_size: 16., color: Color::WHITE, ..default() }),
+                HeatmapLegendLabel,
+            ));
+            legend
+                .spawn(NodeBundle { style: Style { height: Val::Px(12.), ..default() }, ..default() })
+                .with_children(|bar| {
+                    for step in 0..LEGEND_STEPS {
+                        bar.spawn(NodeBundle {
+                            style: Style { width: Val::Px(12.), height: Val::Percent(100.), ..default() },
+                            background_color: gradient(step as f32 / (LEGEND_STEPS - 1) as f32).into(),
+                            ..default()
+                        });
+                    }
+                });
+        });
+}
+
+pub fn change_heatmap_mode(mut heatmap: ResMut<Heatmap>, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    if keyboard_input.just_pressed(KeyCode::KeyV) {
+        heatmap.mode = heatmap.mode.next();
+    }
+}
+
+// Order and visit counts come from the history so they follow the timeline, costs from the solver.
+// Only the steps applied or undone since the last frame are looked at.
+pub fn compute_heatmap(mut heatmap: ResMut<Heatmap>, solver: Res<Solver>, maze: Res<Maze>) {
+    let mode_changed = heatmap.is_changed();
+    if !(mode_changed || solver.is_changed() || maze.is_changed()) {
+        return;
+    }
+    // With the heatmap off there is nothing to follow until the mode changes again.
+    if heatmap.mode == HeatmapMode::Off && heatmap.run.is_none() {
+        return;
+    }
+    let heatmap = &mut *heatmap;
+    let all = || (0..maze.cells.len()).collect();
+    heatmap.changed.clear();
+    if heatmap.mode == HeatmapMode::Off {
+        // The tinted cells get their tiles back.
+        heatmap.run = None;
+        heatmap.changed = all();
+        return;
+    }
+    let history = &solver.history;
+    let reset = mode_changed || heatmap.run != Some(history.run()) || heatmap.visits.len() != maze.cells.len();
+    if reset {
+        heatmap.reset(maze.cells.len(), history.run());
+    }
+    let touched = heatmap.follow(history.steps(), history.cursor(), &maze);
+    let previous_range = heatmap.range;
+    let indices = if reset { all() } else { touched };
+    let mut rescan = false;
+    for &index in indices.iter() {
+        let value = heatmap.value(index, &solver, &maze);
+        let before = std::mem::replace(&mut heatmap.values[index], value);
+        if before == value {
+            continue;
+        }
+        // Losing a value at either end of the range can shrink it, which takes a full scan to tell.
+        if let (Some(before), Some((low, high))) = (before, heatmap.range) {
+            rescan |= before == low || before == high;
+        }
+        if let Some(value) = value {
+            heatmap.range = Some(heatmap.range.map_or((value, value), |(low, high)| (low.min(value), high.max(value))));
+        }
+    }
+    if rescan {
+        let known = heatmap.values.iter().flatten();
+        heatmap.range = known.clone().copied().min().zip(known.copied().max());
+    }
+    // Every tint is relative to the range, so moving it repaints the whole maze.
+    heatmap.changed = if heatmap.range != previous_range { all() } else { indices };
+}
+
+pub fn draw_heatmap(
+    heatmap: Res<Heatmap>,
+    mut query: Query<(&mut Handle<Image>, &mut Sprite), With<Cell>>,
+    maze: Res<Maze>,
+    view: Res<MazeView>,
+    mut images: ResMut<Assets<Image>>,
+    assets: Res<CellAssets>) {
+
+    if !heatmap.is_changed() || heatmap.changed.is_empty() {
+        return;
+    }
+    let (low, high) = heatmap.range.unwrap_or((0, 0));
+    let tint = |index: usize| {
+        let tinted = heatmap.mode != HeatmapMode::Off
+            && !matches!(maze.cells[index], CellState::START | CellState::END | CellState::WALL | CellState::PATH | CellState::FRONTIER);
+        let value = heatmap.values.get(index).copied().flatten().filter(|_| tinted)?;
+        Some(gradient(if high > low { (value - low) as f32 / (high - low) as f32 } else { 0. }))
+    };
+    let indices = heatmap.changed.iter().copied();
+    let entities = match &*view {
+        MazeView::Empty => return,
+        MazeView::Sprites(entities) => entities,
+        MazeView::Image(image) => {
+            image.paint(&mut images, indices, |index| {
+                tint(index).map_or_else(|| visualizer::get_color(&maze.cells[index]), |color| color.as_rgba_u8())
+            });
+            return;
+        }
+    };
+    for index in indices {
+        let Some(Ok((mut texture, mut sprite))) = entities.get(index).map(|&entity| query.get_mut(entity)) else {
+            continue;
+        };
+        match tint(index) {
+            Some(color) => {
+                // The default image is plain white, so the sprite shows the tint as is.
+                *texture = Handle::default();
+                sprite.color = color;
+            }
+            // A cell that was tinted before gets its tile back.
+            None if sprite.color != Color::WHITE => {
+                *texture = visualizer::get_image(&maze.cells[index], &assets);
+                sprite.color = Color::WHITE;
+            }
+            None => {}
+        }
+    }
+}
+
+pub fn update_heatmap_legend(
+    heatmap: Res<Heatmap>,
+    mut legend_query: Query<&mut Visibility, With<HeatmapLegend>>,
+    mut label_query: Query<&mut Text, With<HeatmapLegendLabel>>) {
+
+    if !heatmap.is_changed() {
+        return;
+    }
+    for mut visibility in legend_query.iter_mut() {
+        *visibility = if heatmap.mode == HeatmapMode::Off { Visibility::Hidden } else { Visibility::Visible };
+    }
+    let (low, high) = heatmap.range.unwrap_or((0, 0));
+    for mut label in label_query.iter_mut() {
+        label.sections[0].value = format!("Heatmap: {}\n{} to {}", heatmap.mode.name(), low, high);
+    }
+}
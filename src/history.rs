@@ -0,0 +1,205 @@
+use crate::maze::{CellState, Maze};
+use crate::path_finders::path_finder_interface::SolverEvent;
+use crate::path_finders::stats::SolverStats;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_RUN: AtomicUsize = AtomicUsize::new(0);
+
+/// A cell one solver step changed, with its state on both sides of the step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellChange {
+    pub position: (usize, usize),
+    pub before: CellState,
+    pub after: CellState,
+}
+
+/// Everything one `iterate` call did, enough to undo and redo it on the maze.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub events: Vec<SolverEvent>,
+    pub changes: Vec<CellChange>,
+    /// Cells waiting to be expanded after the step.
+    pub frontier_size: usize,
+    /// The solver's statistics after the step.
+    pub stats: SolverStats,
+}
+
+/// The steps of a solver run, with a cursor that can move back and forth over them.
+///
+/// Solvers are deterministic, so running on from the newest step gives the same steps
+/// as the ones recorded: moving the cursor forward again resumes the run exactly.
+pub struct History {
+    steps: Vec<Step>,
+    // Steps before the cursor are applied to the maze.
+    cursor: usize,
+    // The maze as of the newest step, to tell what the next step changed.
+    cells: Vec<CellState>,
+    initial_frontier_size: usize,
+    initial_stats: SolverStats,
+    run: usize,
+}
+
+impl History {
+    /// An empty history for a solver that has not run yet, with `frontier_size` cells to expand.
+    pub fn new(maze: &Maze, frontier_size: usize) -> Self {
+        Self {
+            steps: vec![],
+            cursor: 0,
+            cells: maze.cells.clone(),
+            initial_frontier_size: frontier_size,
+            initial_stats: SolverStats::default(),
+            run: NEXT_RUN.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Adds the step that just turned the maze into `maze`, leaving the solver with `frontier_size`
+    /// cells to expand and `stats`. Only call it with the cursor at the end.
+    pub fn record(&mut self, events: Vec<SolverEvent>, frontier_size: usize, stats: SolverStats, maze: &Maze) {
+        let mut changes = vec![];
+        let mut compare = |index: usize| {
+            if self.cells[index] != maze.cells[index] {
+                changes.push(CellChange {
+                    position: maze.position_of(index),
+                    before: self.cells[index].clone(),
+                    after: maze.cells[index].clone(),
+                });
+                self.cells[index] = maze.cells[index].clone();
+            }
+        };
+        if events.iter().any(|event| matches!(event, SolverEvent::PathFound(_))) {
+            // Marking the path touches cells the events do not mention.
+            (0..maze.cells.len()).for_each(&mut compare);
+        } else {
+            for event in events.iter() {
+                if let SolverEvent::Expanded(position) | SolverEvent::FrontierPush(position) = event {
+                    if let Some(index) = maze.cell_id(*position) {
+                        compare(index);
+                    }
+                }
+            }
+        }
+        self.steps.push(Step { events, changes, frontier_size, stats });
+        self.cursor = self.steps.len();
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Number of steps applied to the maze.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.cursor == self.steps.len()
+    }
+
+    /// The step the cursor is right after, `None` before the first one.
+    pub fn current(&self) -> Option<&Step> {
+        self.cursor.checked_sub(1).map(|index| &self.steps[index])
+    }
+
+    /// Tells histories apart: no two histories made by one process share this number.
+    pub fn run(&self) -> usize {
+        self.run
+    }
+
+    /// Every recorded step, applied or not, oldest first.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// The steps currently applied to the maze, oldest first.
+    pub fn applied(&self) -> &[Step] {
+        &self.steps[..self.cursor]
+    }
+
+    /// Cells waiting to be expanded at the cursor.
+    pub fn frontier_size(&self) -> usize {
+        self.current().map_or(self.initial_frontier_size, |step| step.frontier_size)
+    }
+
+    /// The solver's statistics at the cursor, so they match the maze while rewound.
+    pub fn stats(&self) -> &SolverStats {
+        self.current().map_or(&self.initial_stats, |step| &step.stats)
+    }
+
+    /// Moves the cursor to `target`, clamped to the recorded steps, undoing or redoing their changes on `maze`.
+    pub fn seek(&mut self, target: usize, maze: &mut Maze) {
+        let target = target.min(self.steps.len());
+        while self.cursor > target {
+            self.cursor -= 1;
+            for change in self.steps[self.cursor].changes.iter().rev() {
+                maze.set(change.position.0, change.position.1, change.before.clone());
+            }
+        }
+        while self.cursor < target {
+            for change in self.steps[self.cursor].changes.iter() {
+                maze.set(change.position.0, change.position.1, change.after.clone());
+            }
+            self.cursor += 1;
+        }
+    }
+
+    /// Events of every recorded step, in the shape `Trace` saves.
+    pub fn events(&self) -> Vec<Vec<SolverEvent>> {
+        self.steps.iter().map(|step| step.events.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{generate_maze, Generator};
+    use crate::path_finders::dfs::DFS;
+    use crate::path_finders::path_finder_interface::PathFinder;
+    use crate::path_finders::solve;
+
+    // A history of a whole DFS run, with the maze cells, frontier size and expansions after every step.
+    fn recorded_run() -> (Maze, History, Vec<Vec<CellState>>, Vec<usize>, Vec<usize>) {
+        let mut maze = generate_maze(15, 10, Generator::Scatter, 3);
+        let mut solver = DFS::new(&maze);
+        let mut history = History::new(&maze, solver.frontier_size());
+        let mut cells = vec![maze.cells.clone()];
+        let mut frontier_sizes = vec![solver.frontier_size()];
+        let mut expanded = vec![0];
+        for (events, frontier_size, stats) in solve(&mut solver, &maze.clone()) {
+            maze.apply_events(&events);
+            frontier_sizes.push(frontier_size);
+            expanded.push(stats.nodes_expanded);
+            history.record(events, frontier_size, stats, &maze);
+            cells.push(maze.cells.clone());
+        }
+        (maze, history, cells, frontier_sizes, expanded)
+    }
+
+    #[test]
+    fn seek_undoes_and_redoes_to_identical_cells() {
+        let (mut maze, mut history, cells, frontier_sizes, expanded) = recorded_run();
+        assert!(history.is_at_end());
+        assert_eq!(history.len(), cells.len() - 1);
+        let end = history.len();
+        for target in [0, end / 2, end, 1, end - 1, 0, end] {
+            history.seek(target, &mut maze);
+            assert_eq!(history.cursor(), target);
+            assert_eq!(maze.cells, cells[target], "cells after seeking to {}", target);
+            assert_eq!(history.frontier_size(), frontier_sizes[target]);
+            assert_eq!(history.stats().nodes_expanded, expanded[target]);
+            assert_eq!(history.applied().len(), target);
+        }
+    }
+
+    #[test]
+    fn seek_clamps_to_the_recorded_steps() {
+        let (mut maze, mut history, cells, _, _) = recorded_run();
+        history.seek(0, &mut maze);
+        history.seek(usize::MAX, &mut maze);
+        assert!(history.is_at_end());
+        assert_eq!(maze.cells, cells[cells.len() - 1]);
+    }
+}
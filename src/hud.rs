@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use mazes::maze::Maze;
+use mazes::path_finders::SOLVERS;
+use mazes::plugin::{Controls, LoadedTrace, MazeState, Solver, StatusMessage, ALGORITHM_KEYS};
+
+const LEGEND: &str = "Space: start/pause
+S/B: step forward/back while paused
+Timeline: drag to rewind
+Left/right drag: draw/erase walls
+Drag start or end to move it
+Mouse: inspect a cell, ctrl-click to pin
+Wheel/middle drag: zoom/pan, F: reset view
+C: race all algorithms
+V: heatmap (cost, order, visits)
+I: solve instantly, again to stop
++/-: faster/slower
+M: per frame/per second
+R: new maze
+1-9: algorithm, listed below
+Arrows: maze size
+T: save trace
+H: hide this panel";
+
+#[derive(Component)]
+pub struct Hud;
+
+// One line per algorithm in `SOLVERS`, with the key that picks it.
+fn algorithm_menu() -> String {
+    SOLVERS
+        .iter()
+        .take(ALGORITHM_KEYS.len())
+        .enumerate()
+        .map(|(index, entry)| {
+            let capabilities = entry.capabilities.names();
+            if capabilities.is_empty() {
+                format!("{}: {}", index + 1, entry.description)
+            } else {
+                format!("{}: {} ({})", index + 1, entry.description, capabilities.join(", "))
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn setup_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section("", TextStyle { font_size: 16., color: Color::WHITE, ..default() })
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.),
+                left: Val::Px(8.),
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            })
+            .with_background_color(Color::rgba(0., 0., 0., 0.75)),
+        Hud,
+    ));
+}
+
+pub fn update_hud(
+    mut query: Query<&mut Text, With<Hud>>,
+    solver: Res<Solver>,
+    maze: Res<Maze>,
+    controls: Res<Controls>,
+    maze_state: Res<State<MazeState>>,
+    message: Res<StatusMessage>,
+    loaded_trace: Option<Res<LoadedTrace>>) {
+
+    let history = &solver.history;
+    let stats = history.stats();
+    let background = solver.background.as_ref();
+    let solver = &solver.solver;
+    // Playing a rewound run redoes the recorded steps before the solver takes over again.
+    let state = match (background, maze_state.get()) {
+        (Some(background), _) if background.is_cancelled() => "stopping",
+        (Some(_), _) => "solving in the background",
+        (None, MazeState::Editing) => "editing",
+        (None, MazeState::Generating) => "generating",
+        (None, MazeState::Solving) => "running",
+        (None, MazeState::Paused) if !history.is_at_end() => "rewound",
+        (None, MazeState::Paused) => "paused",
+        (None, MazeState::Solved) => "solved",
+        (None, MazeState::NoPath) => "no path",
+    };
+    let source = match loaded_trace {
+        Some(_) => "replayed".to_string(),
+        None => format!("{}, seed {}", controls.generator.name(), maze.seed),
+    };
+    let path = match (stats.path_length, stats.path_cost) {
+        (Some(length), Some(cost)) => format!("{} cells, cost {}", length, cost),
+        _ => "-".to_string(),
+    };
+    let mut text = format!(
+        "{} ({}, {})\n{}x{} maze, {}\nStep {} of {}\n\nExpanded: {} ({} again)\nGenerated: {}\nOpen set: {} (peak {})\nMemory: ~{} KiB\nPath: {}\nIterations: {} in {:.2?}\n",
+        solver.name(), state, controls.speed, maze.width, maze.height, source, history.cursor(), history.len(),
+        stats.nodes_expanded, stats.re_expansions, stats.nodes_generated, history.frontier_size(), stats.peak_open_set,
+        stats.peak_memory.div_ceil(1024), path, stats.iterations, stats.elapsed);
+    if !message.0.is_empty() {
+        text.push('\n');
+        text.push_str(&message.0);
+        text.push('\n');
+    }
+    text.push('\n');
+    text.push_str(LEGEND);
+    text.push_str("\n\n");
+    text.push_str(&algorithm_menu());
+    for mut hud in query.iter_mut() {
+        hud.sections[0].value.clone_from(&text);
+    }
+}
+
+pub fn toggle_hud(mut query: Query<&mut Visibility, With<Hud>>, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    if !keyboard_input.just_pressed(KeyCode::KeyH) {
+        return;
+    }
+    for mut visibility in query.iter_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
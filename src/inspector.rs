@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use mazes::maze::Maze;
+use mazes::path_finders::path_finder_interface::CellInfo;
+use mazes::visualizer::{self, CellSize};
+use crate::race::Race;
+use crate::timeline::Timeline;
+use mazes::plugin::Solver;
+
+/// The cell under the mouse, and the one clicked to keep it on screen.
+#[derive(Resource, Default)]
+pub struct Inspector {
+    hovered: Option<(usize, usize)>,
+    pinned: Option<(usize, usize)>,
+}
+
+#[derive(Component)]
+pub struct InspectorPanel;
+
+pub fn setup_inspector(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section("", TextStyle { font_size: 16., color: Color::WHITE, ..default() })
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.),
+                right: Val::Px(8.),
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            })
+            .with_background_color(Color::rgba(0., 0., 0., 0.75)),
+        InspectorPanel,
+    ));
+}
+
+// Ctrl-clicking a cell pins it, doing it again lets the panel follow the mouse again.
+#[allow(clippy::too_many_arguments)]
+pub fn inspect_cells(
+    mut inspector: ResMut<Inspector>,
+    window_query: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    timeline_query: Query<&Interaction, With<Timeline>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    maze: Res<Maze>,
+    cell_size: Res<CellSize>,
+    race: Option<Res<Race>>) {
+
+    // The race panels are not the maze the solver runs on.
+    if race.is_some() {
+        inspector.hovered = None;
+        inspector.pinned = None;
+        return;
+    }
+    let window = window_query.single();
+    let (camera, camera_transform) = camera_query.single();
+    inspector.hovered = visualizer::cell_under_cursor(window, camera, camera_transform, &maze, cell_size.0 as f32);
+    let on_timeline = timeline_query.iter().any(|interaction| *interaction != Interaction::None);
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if mouse_input.just_pressed(MouseButton::Left) && ctrl && !on_timeline {
+        inspector.pinned = match inspector.hovered {
+            Some(cell) if inspector.pinned != Some(cell) => Some(cell),
+            _ => None,
+        };
+    }
+}
+
+pub fn update_inspector_panel(
+    mut query: Query<(&mut Text, &mut Visibility), With<InspectorPanel>>,
+    inspector: Res<Inspector>,
+    solver: Res<Solver>,
+    maze: Res<Maze>) {
+
+    let cell = inspector
+        .pinned
+        .or(inspector.hovered)
+        .filter(|&(x, y)| x < maze.width && y < maze.height);
+    for (mut text, mut visibility) in query.iter_mut() {
+        let Some((x, y)) = cell else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Visible;
+        let mut info = solver.solver.cell_info(&maze, (x, y));
+        // The solver is at the end of the run. While rewound, only the cells expanded by the
+        // cursor have settled, whatever it knows about the others comes later.
+        let rewound = !solver.history.is_at_end();
+        let stats = solver.history.stats();
+        if rewound && info.visit_order.is_none_or(|order| order >= stats.nodes_expanded - stats.re_expansions) {
+            info = CellInfo::default();
+        }
+        let value = |value: Option<usize>| value.map_or("-".to_string(), |value| value.to_string());
+        let mut content = format!(
+            "Cell ({}, {}): {}\ng: {}  h: {}  f: {}\nParent: {}\nExpanded: {}",
+            x, y, format!("{:?}", maze.get(x, y)).to_lowercase(),
+            value(info.g), value(info.h), value(info.f),
+            info.parent.map_or("-".to_string(), |(px, py)| format!("({}, {})", px, py)),
+            info.visit_order.map_or("no".to_string(), |order| format!("#{}", order + 1)));
+        if rewound {
+            content.push_str(&format!("\nAs of step {}", solver.history.cursor()));
+        }
+        if inspector.pinned.is_some() {
+            content.push_str("\n\nPinned, ctrl-click it again to release");
+        }
+        text.sections[0].value = content;
+    }
+}
@@ -0,0 +1,33 @@
+//! Maze generation and step-by-step path finding.
+//!
+//! The maze model, the generators and the solvers do not depend on Bevy.
+//! The Bevy rendering lives in `visualizer` and `plugin`, behind the
+//! `visualizer` cargo feature (on by default). `plugin::MazeVisualizerPlugin`
+//! adds the whole visualizer to a Bevy app. Build with
+//! `default-features = false` to use the solvers without a renderer.
+
+pub mod background;
+pub mod cli;
+pub mod history;
+pub mod maze;
+pub mod path_finders;
+#[cfg(feature = "visualizer")]
+pub mod plugin;
+pub mod run;
+pub mod speed;
+pub mod trace;
+#[cfg(feature = "visualizer")]
+pub mod visualizer;
+
+pub use maze::{CellState, Generator, Maze, MazeChanges};
+pub use path_finders::path_finder_interface::{PathFinder, SolverEvent};
+pub use path_finders::stats::SolverStats;
+
+/// The error `parse` gives for `input`, failing the test if it parses.
+#[cfg(test)]
+pub(crate) fn parse_error<T, E: std::fmt::Display>(parse: impl Fn(&str) -> Result<T, E>, input: &str) -> String {
+    match parse(input) {
+        Ok(_) => panic!("parsed `{}`", input),
+        Err(err) => err.to_string(),
+    }
+}
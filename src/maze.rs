@@ -1,57 +1,72 @@
-use bevy::asset::Handle;
-use bevy::ecs::entity::Entity;
-use bevy::prelude::{Color, Commands, Component, Query, Res, Resource, Sprite, SpriteBundle, Transform, Window, With};
-use bevy::math::Vec2;
-use bevy::render::texture::Image;
-use bevy::utils::dbg;
 use std::collections::VecDeque;
-use rand::{thread_rng, Rng};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 use rand::prelude::SliceRandom;
+use crate::path_finders::path_finder_interface::SolverEvent;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub enum CellState {
     START,
     UNEXPLORED,
     EXPLORED,
+    /// Waiting in a solver's open set to be expanded.
+    FRONTIER,
     WALL,
     PATH,
     END,
 }
 
-#[derive(Component)]
-pub struct Cell{
-    pub position: (usize, usize),
-    pub type_: CellState
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Generator {
+    /// Recursive backtracker: a perfect maze, there is exactly one path between two cells.
+    Backtracker,
+    /// Walls scattered over an open field, so there are many routes of different lengths.
+    Scatter,
 }
 
+impl Generator {
+    pub const ALL: [Generator; 2] = [Generator::Backtracker, Generator::Scatter];
 
-#[derive(Resource)]
-pub struct CellAssets {
-    pub start_tile: Handle<Image>,
-    pub end_tile: Handle<Image>,
-    pub wall_tile: Handle<Image>,
-    pub unexplored_tile: Handle<Image>,
-    pub explored_tile: Handle<Image>,
-    pub path_tile: Handle<Image>,
+    pub fn name(&self) -> &'static str {
+        match self {
+            Generator::Backtracker => "backtracker",
+            Generator::Scatter => "scatter",
+        }
+    }
 
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|generator| generator.name() == name)
+    }
 }
-#[derive(Resource)]
-pub struct CellSize(pub usize);
 
 fn get_index(point: (usize,usize), width: usize) -> usize {
     point.1 * width + point.0
 }
 
 
-fn carve_maze(x: usize, y: usize, width: usize, height: usize, maze: &mut Vec<bool>) {
-    let mut rng = thread_rng();
-    let directions: [(isize, isize); 4] = [(2 , 0), (-2 , 0 ), (0 , 2 ), (0 , -2 )];
-    let mut shuffled_directions = directions.to_vec();
-    shuffled_directions.shuffle(&mut rng);
+// A cell being carved from and the directions it has not tried yet, in the order it tries them.
+type CarveEntry = (usize, usize, Vec<(isize, isize)>);
+
+fn shuffled_directions(rng: &mut StdRng) -> Vec<(isize, isize)> {
+    let mut directions = vec![(2 , 0), (-2 , 0 ), (0 , 2 ), (0 , -2 )];
+    directions.shuffle(rng);
+    // Popped from the back, so reversed to try them in shuffled order.
+    directions.reverse();
+    directions
+}
 
-    for &(dx, dy) in shuffled_directions.iter() {
-        let nx = x as isize + dx as isize;
-        let ny = y as isize + dy as isize;
+// Depth first, on an explicit stack: a path can be as long as the maze has cells, too deep to recurse.
+fn carve_maze(x: usize, y: usize, width: usize, height: usize, maze: &mut [bool], rng: &mut StdRng) {
+    let mut stack: Vec<CarveEntry> = vec![(x, y, shuffled_directions(rng))];
+    while let Some((x, y, remaining)) = stack.last_mut() {
+        let (x, y) = (*x, *y);
+        let Some((dx, dy)) = remaining.pop() else {
+            stack.pop();
+            continue;
+        };
+        let nx = x as isize + dx;
+        let ny = y as isize + dy;
 
         if nx > 0 && (nx as usize)< width  && ny > 0 && (ny as usize) < height  {
             let nx = nx as usize;
@@ -61,20 +76,28 @@ fn carve_maze(x: usize, y: usize, width: usize, height: usize, maze: &mut Vec<bo
                 let mid_y = (y as isize + (dy / 2)) as usize;
                 maze[mid_y * width + mid_x] = false;
                 maze[ny * width + nx] = false;
-                carve_maze(nx, ny, width, height, maze);
+                stack.push((nx, ny, shuffled_directions(rng)));
             }
         }
     }
 }
 
-fn get_appropriate_endpoint(maze: &Vec<bool>, width: usize, height: usize, start: (usize,usize)) -> (usize,usize) {
+// Share of cells that become walls with `Generator::Scatter`.
+const SCATTER_WALL_DENSITY: f64 = 0.3;
+
+fn scatter_walls(start: (usize, usize), width: usize, maze: &mut [bool], rng: &mut StdRng) {
+    for (index, cell) in maze.iter_mut().enumerate() {
+        *cell = index != get_index(start, width) && rng.gen_bool(SCATTER_WALL_DENSITY);
+    }
+}
+
+fn get_appropriate_endpoint(maze: &Maze, start: (usize,usize), rng: &mut StdRng) -> (usize,usize) {
     let mut queue = VecDeque::new();
-    let mut visited = vec![false; width * height]; // Visited flag for each cell
+    let mut visited = vec![false; maze.cells.len()]; // Visited flag for each cell
     let mut furthest_points = vec![];
     queue.push_back((start, 0)); // (x, y, distance)
-    visited[get_index(start, width)] = true;
+    visited[get_index(start, maze.width)] = true;
 
-    let mut farthest_point = start;
     let mut max_distance = 0;
 
     while let Some((point, dist)) = queue.pop_front() {
@@ -83,16 +106,12 @@ fn get_appropriate_endpoint(maze: &Vec<bool>, width: usize, height: usize, start
             max_distance = dist;
             furthest_points.push(point);
         }
-        let directions: Vec<(usize,usize)> = [(0, 1), (0, -1), (1, 0), (-1, 0)]
-                        .iter()
-                        .map(|&(x,y)| (point.0 as isize + x, point.1 as isize + y))
-                        .filter(|&(nx,ny)| nx >= 0 && nx < width as isize && ny >= 0 && ny < height as isize)
-                        .map(|(nx,ny)| (nx as usize, ny as usize))
-                        .filter(|&(nx,ny)| !visited[get_index((nx,ny), width)] && !maze[get_index((nx,ny), width)]).collect::<Vec<(usize,usize)>>();
         // Explore neighbors
-        for &new_point in &directions {
-            visited[get_index(new_point, width)] = true;
-            queue.push_back((new_point, dist + 1));
+        for new_point in maze.neighbours(point) {
+            if !visited[get_index(new_point, maze.width)] {
+                visited[get_index(new_point, maze.width)] = true;
+                queue.push_back((new_point, dist + 1));
+            }
         }
     }
     furthest_points.sort_by(|&a, &b| {
@@ -101,150 +120,281 @@ fn get_appropriate_endpoint(maze: &Vec<bool>, width: usize, height: usize, start
         dist_a.cmp(&dist_b)
     });
 
+    // Nothing is reachable from the start, so the maze is solved where it begins.
+    if furthest_points.is_empty() {
+        return start;
+    }
     let half_index = furthest_points.len() / 2;
     let biggest_half = &furthest_points[half_index..];
 
     // Step 3: Pick a random element from the biggest half
-    let mut rng = thread_rng();
     let random_index = rng.gen_range(0..biggest_half.len());
     biggest_half[random_index]
 }
 
 pub fn create_maze(width: usize, height: usize) -> Maze {
+    create_seeded_maze(width, height, rand::random())
+}
+
+/// Same as `create_maze`, but the same seed always carves the same maze.
+pub fn create_seeded_maze(width: usize, height: usize, seed: u64) -> Maze {
+    generate_maze(width, height, Generator::Backtracker, seed)
+}
+
+/// Panics if `width` or `height` is 0, a maze needs a cell for its start and end.
+pub fn generate_maze(width: usize, height: usize, generator: Generator, seed: u64) -> Maze {
+    assert!(width > 0 && height > 0, "Maze size {}x{} has no cells, both sides must be above 0.", width, height);
+    let mut rng = StdRng::seed_from_u64(seed);
     // First create bitmap of the maze to run the carving algorithm to.
     let mut bit_maze = vec![true; width * height];
-    let start_x = rand::random::<usize>() % width;
-    let start_y = rand::random::<usize>() % height;
+    let start_x = rng.gen::<usize>() % width;
+    let start_y = rng.gen::<usize>() % height;
     bit_maze[start_y * width + start_x] = false;
-    carve_maze(start_x, start_y, width, height, &mut bit_maze);
     let start = (start_x, start_y);
-    let end = get_appropriate_endpoint(&bit_maze, width, height, start);
-    let mut cells = bit_maze
+    match generator {
+        Generator::Backtracker => carve_maze(start_x, start_y, width, height, &mut bit_maze, &mut rng),
+        Generator::Scatter => scatter_walls(start, width, &mut bit_maze, &mut rng),
+    }
+    let cells = bit_maze
                     .into_iter()
                     .map(|cell| if cell{CellState::WALL} else {CellState::UNEXPLORED})
                     .collect::<Vec<CellState>>();
-    cells[get_index(start, width)] = CellState::START;
-    cells[get_index(end, width)] = CellState::END;
-    Maze {
-        start,
-        end,
-        width,
-        height,
-        cells,
+    let mut maze = Maze::new(width, height, cells, start, start, seed).expect("the generator fills every cell");
+    maze.end = get_appropriate_endpoint(&maze, start, &mut rng);
+    maze.cells[get_index(start, width)] = CellState::START;
+    maze.cells[get_index(maze.end, width)] = CellState::END;
+    maze
+}
+
+/// Cells that changed since `Maze::take_changes` was last called, so a renderer only redraws those.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MazeChanges {
+    Cells(Vec<(usize, usize)>),
+    /// Too many to list, or the cells were replaced wholesale.
+    All,
+}
+
+impl MazeChanges {
+    /// Indices into `Maze::cells` of the changed cells of `maze`.
+    pub fn indices(self, maze: &Maze) -> Vec<usize> {
+        match self {
+            MazeChanges::Cells(cells) => cells.into_iter().filter_map(|position| maze.cell_id(position)).collect(),
+            MazeChanges::All => (0..maze.cells.len()).collect(),
+        }
     }
 }
 
-#[derive(Resource, Debug)]
+#[cfg_attr(feature = "visualizer", derive(bevy::prelude::Resource))]
+#[derive(Debug, Clone)]
 pub struct Maze {
-    pub(crate) start: (usize,usize),
-    pub(crate) end: (usize,usize),
-    pub(crate) width: usize,
-    pub(crate) height: usize,
-    pub(crate) cells: Vec<CellState>,
+    pub start: (usize,usize),
+    pub end: (usize,usize),
+    pub width: usize,
+    pub height: usize,
+    /// Writing to `cells` directly bypasses change tracking, call `mark_all_changed` afterwards.
+    pub cells: Vec<CellState>,
+    pub seed: u64,
+    pub(crate) changes: MazeChanges,
 }
 
-impl Maze {
-    pub fn get(&self, x: usize, y:usize) -> &CellState {
-        if x > self.width && y > self.height {
-            panic!("Position: {},{} is invalid.",x,y);
+// The cells next to one another, in the order solvers and generators visit them.
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
 
+impl Maze {
+    /// A maze made elsewhere, such as a game's own map, for the solvers to run on. `cells` lists the
+    /// rows from `y = 0` up, `width` cells each, and `start` and `end` must lie inside the maze.
+    pub fn new(width: usize, height: usize, cells: Vec<CellState>, start: (usize, usize), end: (usize, usize), seed: u64) -> Result<Maze, String> {
+        if width.checked_mul(height) != Some(cells.len()) {
+            return Err(format!("expected {}x{} cells, found {}", width, height, cells.len()));
         }
-        &self.cells[y*self.width+x]
+        let inside = |(x, y): (usize, usize)| x < width && y < height;
+        if !inside(start) || !inside(end) {
+            return Err(format!("start {},{} and end {},{} must lie inside the maze", start.0, start.1, end.0, end.1));
+        }
+        Ok(Maze { start, end, width, height, cells, seed, changes: MazeChanges::All })
+    }
+    /// Id of the cell at `position`, its index into `cells`, or `None` outside the maze.
+    pub fn cell_id(&self, (x, y): (usize, usize)) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| get_index((x, y), self.width))
+    }
+    /// Position of the cell with id `id`, or `None` if there is no such cell.
+    pub fn position(&self, id: usize) -> Option<(usize, usize)> {
+        (id < self.cells.len()).then(|| (id % self.width, id / self.width))
+    }
+    // The solvers' take on `cell_id` and `position`: their cells all come from the maze, so
+    // anything else is a bug and panics like `get`.
+    pub(crate) fn id_at(&self, position: (usize, usize)) -> usize {
+        self.cell_id(position).unwrap_or_else(|| panic!("Position: {},{} is invalid.", position.0, position.1))
     }
+    pub(crate) fn position_of(&self, id: usize) -> (usize, usize) {
+        self.position(id).unwrap_or_else(|| panic!("Cell id {} is invalid.", id))
+    }
+    /// The cell at `(x, y)`, or `None` outside the maze.
+    pub fn try_get(&self, x: usize, y: usize) -> Option<&CellState> {
+        self.cell_id((x, y)).map(|id| &self.cells[id])
+    }
+    /// Sets the cell at `(x, y)`, or returns `None` without changing anything outside the maze.
+    pub fn try_set(&mut self, x: usize, y: usize, state: CellState) -> Option<()> {
+        let id = self.cell_id((x, y))?;
+        self.cells[id] = state;
+        if let MazeChanges::Cells(changed) = &mut self.changes {
+            changed.push((x, y));
+            // Nobody is taking the changes, or nearly everything changed: stop listing them.
+            if changed.len() > self.cells.len() {
+                self.changes = MazeChanges::All;
+            }
+        }
+        Some(())
+    }
+    /// Panics outside the maze, `try_get` does not.
+    pub fn get(&self, x: usize, y:usize) -> &CellState {
+        self.try_get(x, y).unwrap_or_else(|| panic!("Position: {},{} is invalid.",x,y))
+    }
+    /// Panics outside the maze, `try_set` does not.
     pub fn set(&mut self, x: usize, y:usize, state: CellState) {
-        if x > self.width && y > self.height {
+        if self.try_set(x, y, state).is_none() {
             panic!("Position: {},{} is invalid.",x,y);
+        }
+    }
+    /// The cells a solver can move to from `position`: the four next to it that lie inside the maze
+    /// and are not walls.
+    pub fn neighbours(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRECTIONS
+            .into_iter()
+            .filter_map(move |(dx, dy)| Some((position.0.checked_add_signed(dx)?, position.1.checked_add_signed(dy)?)))
+            .filter(|&(x, y)| self.try_get(x, y).is_some_and(|cell| cell != &CellState::WALL))
+    }
+
+    pub fn mark_all_changed(&mut self) {
+        self.changes = MazeChanges::All;
+    }
 
+    /// Returns the cells changed since the last call and starts tracking afresh.
+    pub fn take_changes(&mut self) -> MazeChanges {
+        std::mem::replace(&mut self.changes, MazeChanges::Cells(vec![]))
+    }
+    /// Marks `(x, y)` as on the frontier, unless it already shows something else than unexplored.
+    pub fn mark_frontier(&mut self, x: usize, y: usize) {
+        if self.try_get(x, y) == Some(&CellState::UNEXPLORED) {
+            self.set(x, y, CellState::FRONTIER);
         }
-        self.cells[y*self.width+x] = state;
     }
     pub fn reset_explored_paths(&mut self) {
         self.cells = self.cells.iter().map(|cell| {
             match cell {
                 CellState::EXPLORED => CellState::UNEXPLORED,
+                CellState::FRONTIER => CellState::UNEXPLORED,
                 CellState::PATH => CellState::UNEXPLORED,
                 _ => cell.clone()
             }
         }).collect::<Vec<CellState>>();
+        self.mark_all_changed();
+        self.set(self.start.0, self.start.1, CellState::START);
+        self.set(self.end.0, self.end.1, CellState::END);
     } 
-}
-
-fn get_color(cell: &CellState) -> Color {
-    match cell {
-        CellState::START => Color::GREEN,
-        CellState::END => Color::RED,
-        CellState::WALL => Color::BLACK,
-        CellState::UNEXPLORED => Color::WHITE,
-        CellState::EXPLORED => Color::GRAY,
-        CellState::PATH => Color::BLUE,
-    }
-}
-
-fn get_image(cell: &CellState, assets: &Res<CellAssets>) -> Handle<Image> {
-    match cell {
-        CellState::START => assets.start_tile.clone(),
-        CellState::END => assets.end_tile.clone(),
-        CellState::WALL => assets.wall_tile.clone(),
-        CellState::UNEXPLORED => assets.unexplored_tile.clone(),
-        CellState::EXPLORED => assets.explored_tile.clone(),
-        CellState::PATH => assets.path_tile.clone(),
-    }
-}
-
-pub fn render_maze(
-    mut commands: Commands, 
-    maze: Res<Maze>, 
-    assets: Res<CellAssets>,
-    cell_size: Res<CellSize>,
-    query: Query<Entity, With<Cell>>, 
-    window_query: Query<&Window>) {
-    
-    for cell in query.iter() {
-        commands.entity(cell).remove::<Cell>();
-        commands.entity(cell).despawn();
-    }
-    let window = window_query.single();
-    let x_offset = window.resolution.width() / 2.;
-    let y_offset = window.resolution.height() / 2.;
-    for y in 0..maze.height {
-        for x in 0..maze.width {
-            let index = y * maze.width + x;
-            let cell = &maze.cells[index];
-            let texture = get_image(cell, &assets);
-            commands.spawn(get_tile_sprite(x as f32, x_offset, y as f32, y_offset, cell_size.0 as f32, cell, texture));
+    /// Shows what a solver did in one step: expanded cells turn explored, pushed ones join the
+    /// frontier and a found path is marked. Solvers only read the maze, this is how it shows their progress.
+    /// Cells outside the maze, which only a hand-edited trace can hold, are left out.
+    pub fn apply_events(&mut self, events: &[SolverEvent]) {
+        for event in events {
+            match event {
+                SolverEvent::Expanded((x, y)) => {
+                    if matches!(self.try_get(*x, *y), Some(CellState::UNEXPLORED | CellState::FRONTIER)) {
+                        self.set(*x, *y, CellState::EXPLORED);
+                    }
+                }
+                SolverEvent::FrontierPush((x, y)) => self.mark_frontier(*x, *y),
+                SolverEvent::PathFound(path) => self.mark_path(path),
+            }
         }
     }
-}
-
-fn get_tile_sprite(x: f32, x_offset: f32, y: f32, y_offset: f32, cell_size: f32, cell: &CellState, texture: Handle<Image>) -> (SpriteBundle, Cell){
-        let x_pos = (x * cell_size) - x_offset;
-        let y_pos = (y *  cell_size) - y_offset;
-        (SpriteBundle {
-            transform: Transform::from_xyz(x_pos, y_pos, 0.0),
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(cell_size, cell_size)),
-                ..Default::default()},
-            texture,
-            ..Default::default()
-        },
-        Cell {
-        position: (x as usize,y as usize),
-        type_: cell.clone()
+    /// Shows `path` as the solution: explored and frontier cells go back to unexplored and the path is drawn over them.
+    pub fn mark_path(&mut self, path: &[(usize,usize)]) {
+        for cell in self.cells.iter_mut() {
+            if cell == &CellState::EXPLORED || cell == &CellState::FRONTIER {
+                *cell = CellState::UNEXPLORED;
+            }
+        }
+        self.mark_all_changed();
+        for &(x,y) in path.iter() {
+            self.try_set(x,y, CellState::PATH);
         }
-    )
+        self.set(self.start.0, self.start.1, CellState::START);
+        self.set(self.end.0, self.end.1, CellState::END);
+    }
 }
-pub fn update_maze(maze: Res<Maze>, mut query:  Query<(&Cell, &mut Handle<Image>)>, assets: Res<CellAssets>) {
-    
-    for (cell, mut texture) in query.iter_mut() {
-        match cell.type_ {
-            CellState::END | CellState::START | CellState::WALL => {
 
-            }
-            _ => {
-                *texture = get_image(&maze.get(cell.position.0 as usize, cell.position.1 as usize), &assets);
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // S . .
+    // . # E   with row 0 at the top.
+    fn small_maze() -> Maze {
+        use CellState::*;
+        let mut maze = Maze::new(3, 2, vec![START, UNEXPLORED, UNEXPLORED, UNEXPLORED, WALL, END], (0, 0), (2, 1), 0).unwrap();
+        maze.take_changes();
+        maze
+    }
+
+    #[test]
+    fn new_checks_the_cells_and_endpoints() {
+        let cells = || vec![CellState::UNEXPLORED; 6];
+        assert!(Maze::new(3, 2, cells(), (0, 0), (2, 1), 0).is_ok());
+        assert_eq!(Maze::new(3, 3, cells(), (0, 0), (2, 1), 0).unwrap_err(), "expected 3x3 cells, found 6");
+        assert_eq!(Maze::new(usize::MAX, 2, cells(), (0, 0), (2, 1), 0).unwrap_err(), format!("expected {}x2 cells, found 6", usize::MAX));
+        assert_eq!(Maze::new(3, 2, cells(), (0, 0), (3, 1), 0).unwrap_err(), "start 0,0 and end 3,1 must lie inside the maze");
+        assert_eq!(Maze::new(3, 2, cells(), (0, 2), (2, 1), 0).unwrap_err(), "start 0,2 and end 2,1 must lie inside the maze");
+    }
+
+    #[test]
+    fn cell_ids_stay_inside_the_maze() {
+        let maze = small_maze();
+        assert_eq!(maze.cell_id((0, 0)), Some(0));
+        assert_eq!(maze.cell_id((2, 1)), Some(5));
+        // `y * width + x` would alias these with cells of the next row or past the end.
+        assert_eq!(maze.cell_id((3, 0)), None);
+        assert_eq!(maze.cell_id((0, 2)), None);
+        assert_eq!(maze.cell_id((usize::MAX, usize::MAX)), None);
+        for id in 0..maze.cells.len() {
+            assert_eq!(maze.position(id).and_then(|position| maze.cell_id(position)), Some(id));
         }
+        assert_eq!(maze.position(6), None);
+    }
+
+    #[test]
+    fn try_accessors_reject_positions_outside_the_maze() {
+        let mut maze = small_maze();
+        assert_eq!(maze.try_get(1, 1), Some(&CellState::WALL));
+        assert_eq!(maze.try_get(3, 0), None);
+        assert_eq!(maze.try_get(0, 2), None);
+        assert_eq!(maze.try_set(3, 0, CellState::PATH), None);
+        assert_eq!(maze.take_changes(), MazeChanges::Cells(vec![]));
+        assert_eq!(maze.try_set(1, 0, CellState::PATH), Some(()));
+        assert_eq!(maze.get(1, 0), &CellState::PATH);
+        assert_eq!(maze.take_changes(), MazeChanges::Cells(vec![(1, 0)]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Position: 3,0 is invalid.")]
+    fn get_panics_outside_the_maze() {
+        small_maze().get(3, 0);
     }
-}
 
+    #[test]
+    fn neighbours_skip_walls_and_edges() {
+        let maze = small_maze();
+        let neighbours = |position| maze.neighbours(position).collect::<Vec<(usize, usize)>>();
+        assert_eq!(neighbours((0, 0)), vec![(0, 1), (1, 0)]);
+        assert_eq!(neighbours((1, 0)), vec![(2, 0), (0, 0)]);
+        assert_eq!(neighbours((2, 1)), vec![(2, 0)]);
+        // `(3, 0)` is past the right edge, `y * width + x` would have found the open cell at `(0, 1)`.
+        assert_eq!(neighbours((2, 0)), vec![(2, 1), (1, 0)]);
+    }
 
+    #[test]
+    #[should_panic(expected = "has no cells")]
+    fn empty_mazes_are_rejected() {
+        generate_maze(0, 5, Generator::Backtracker, 1);
+    }
+}
@@ -1,95 +1,111 @@
 use min_max_heap::MinMaxHeap;
-use std::collections::{HashMap, HashSet};
+use std::mem::size_of;
+use std::time::Instant;
 use crate::maze::{CellState, Maze};
 
-use super::path_finder_interface::PathFinder;
+use super::path_finder_interface::{CellInfo, PathFinder, SolverEvent};
+use super::stats::SolverStats;
+use super::{known, UNSET};
 
 pub struct AStar {
-    close_set: HashSet<(usize, usize)>, 
-    came_from: HashMap<(usize, usize), (usize, usize)>,
-    gscore: HashMap<(usize, usize), usize>,
-    fscore: HashMap<(usize, usize), usize>,
+    closed: Vec<bool>,
+    came_from: Vec<usize>,
+    gscore: Vec<usize>,
+    fscore: Vec<usize>,
     heap: MinMaxHeap<(usize, (usize, usize))>,
+    visit_order: Vec<usize>,
+    expanded: usize,
     final_coords: Option<(usize, usize)>,
-    traversed_cells: usize,
-
+    stats: SolverStats,
 }
 
 impl AStar {
     pub fn new(maze: &crate::maze::Maze) -> Self {
-        let mut gscore = HashMap::new();
-        gscore.insert((maze.start.0 as usize, maze.start.1 as usize), 0);
-        let mut fscore = HashMap::new();
-        fscore.insert((maze.start.0 as usize, maze.start.1 as usize), Self::heuristic(maze.start.0 as usize, maze.start.1 as usize, maze.end.0 as usize,maze.end.1 as usize));
+        let cells = maze.cells.len();
+        let start = maze.id_at(maze.start);
+        let mut gscore = vec![UNSET; cells];
+        gscore[start] = 0;
+        let mut fscore = vec![UNSET; cells];
+        fscore[start] = Self::heuristic(maze.start.0, maze.start.1, maze.end.0,maze.end.1);
         let mut heap = MinMaxHeap::new();
-        heap.push((*fscore.get(&(maze.start.0 as usize, maze.start.1 as usize)).unwrap(), (maze.start.0 as usize, maze.start.1 as usize)));
+        heap.push((fscore[start], (maze.start.0, maze.start.1)));
         Self {
-            close_set: HashSet::new(),
-            came_from: HashMap::new(),
-            gscore: gscore,
-            fscore: fscore,
+            closed: vec![false; cells],
+            came_from: vec![UNSET; cells],
+            gscore,
+            fscore,
             heap,
+            visit_order: vec![UNSET; cells],
+            expanded: 0,
             final_coords: None,
-            traversed_cells: 0,
+            stats: SolverStats::default(),
         }
     }
     fn heuristic(x0: usize, y0: usize, x1: usize, y1: usize) -> usize {
         ((x0 as isize - x1 as isize).abs() + (y0 as isize - y1 as isize).abs()) as usize
     }
-}
-
-
-impl PathFinder for AStar {
-    fn iterate(&mut self, maze: &mut crate::maze::Maze) {
-        if self.heap.is_empty() {
-            return;
-        }
+    fn memory_estimate(&self) -> usize {
+        self.closed.len() * size_of::<bool>()
+            + (self.came_from.len() + self.gscore.len() + self.fscore.len() + self.visit_order.len()) * size_of::<usize>()
+            + self.heap.len() * size_of::<(usize, (usize, usize))>()
+    }
+    fn step(&mut self, maze: &Maze) -> Vec<SolverEvent> {
         let (_, (x, y)) = self.heap.pop_min().unwrap();
+        let current = maze.id_at((x,y));
+        // A cell pushed again with a better score leaves its outdated entry in the heap.
+        if self.closed[current] {
+            return vec![];
+        }
         if maze.get(x,y) == &CellState::END {
             self.final_coords = Some((x,y));
-            return;
+            return vec![SolverEvent::PathFound(self.get_path(maze))];
         }
-        self.close_set.insert((x,y));
-        maze.set(x,y, CellState::EXPLORED);
-        self.traversed_cells += 1;
-        let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)]
-            .into_iter()
-            .map(|(dx,dy)| (x as isize + dx,  y as isize + dy))
-            .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && nx < maze.width as isize && ny < maze.height as isize)
-            .map(|(nx, ny)| (nx as usize, ny as usize))
-            .filter(|&(nx, ny)| maze.get(nx,ny) != &CellState::WALL)
-            .collect::<Vec<(usize, usize)>>();
-        
-        for &(nx, ny) in directions.iter() {
-            let tentative_gscore = *self.gscore.get(&(x,y)).unwrap() + 1;
-            if self.gscore.contains_key(&(nx,ny)) &&  tentative_gscore >= *self.gscore.get(&(nx,ny)).unwrap(){
+        let mut events = vec![SolverEvent::Expanded((x,y))];
+        self.closed[current] = true;
+        self.visit_order[current] = self.expanded;
+        self.expanded += 1;
+        for (nx, ny) in maze.neighbours((x,y)) {
+            let next = maze.id_at((nx,ny));
+            let tentative_gscore = self.gscore[current] + 1;
+            // Unknown scores are `UNSET`, larger than any real one.
+            if tentative_gscore >= self.gscore[next] {
                 continue;
             }
-            if tentative_gscore < *self.gscore.get(&(nx,ny)).unwrap_or(&usize::MAX) {
-                self.came_from.insert((nx,ny), (x,y));
-                self.gscore.insert((nx,ny), tentative_gscore);
-                self.fscore.insert((nx,ny), tentative_gscore + Self::heuristic(nx,ny, maze.end.0 as usize, maze.end.1 as usize));
-                self.heap.push((*self.fscore.get(&(nx,ny)).unwrap(), (nx,ny)));
-            }
-            
+            self.came_from[next] = current;
+            self.gscore[next] = tentative_gscore;
+            self.fscore[next] = tentative_gscore + Self::heuristic(nx,ny, maze.end.0, maze.end.1);
+            self.heap.push((self.fscore[next], (nx,ny)));
+            events.push(SolverEvent::FrontierPush((nx,ny)));
         }
+        events
     }
+}
 
-    fn get_path(&self, maze: & Maze) -> Vec<(usize,usize)> {
-        let mut current = self.final_coords;
 
-        // Check if there are final coordinates to start from
-        if current.is_none() {
-            return vec![];  // No final coordinates, not solved
+impl PathFinder for AStar {
+    fn iterate(&mut self, maze: &Maze) -> Vec<SolverEvent> {
+        if self.is_solved() || self.heap.is_empty() {
+            return vec![];
         }
+        let started = Instant::now();
+        let events = self.step(maze);
+        self.stats.record(&events, started.elapsed());
+        self.stats.record_open_set(self.heap.len(), self.memory_estimate());
+        events
+    }
 
+    fn get_path(&self, maze: & Maze) -> Vec<(usize,usize)> {
+        // No final coordinates, not solved
+        let Some(end) = self.final_coords else {
+            return vec![];
+        };
         let mut path = vec![];
-
-        while let Some((x,y)) = current {
-            path.push((x,y));
-            current = self.came_from.get(&(x,y)).cloned();
+        let mut current = Some(maze.id_at(end));
+        while let Some(id) = current {
+            path.push(maze.position_of(id));
+            current = known(self.came_from[id]);
         }
-
+        path.reverse();
         path
 
     }
@@ -97,12 +113,38 @@ impl PathFinder for AStar {
     fn is_solved(&self) -> bool {
         self.final_coords.is_some()
     }
+
+    fn is_exhausted(&self) -> bool {
+        !self.is_solved() && self.heap.is_empty()
+    }
     
-    fn get_accuracy(&self, maze: &Maze) -> f32  {
-         self.get_path(maze).len() as f32 / self.traversed_cells as f32
+    fn get_stats(&self) -> &SolverStats {
+        &self.stats
     }
     
     fn get_new_solver(&mut self, maze: &Maze) -> Box<dyn PathFinder + Sync + Send>{
         Box::new(AStar::new(maze))
     }
-}
\ No newline at end of file
+
+    fn name(&self) -> &str {
+        "A*"
+    }
+
+    fn frontier_size(&self) -> usize {
+        self.heap.len()
+    }
+
+    fn cell_info(&self, maze: &Maze, position: (usize, usize)) -> CellInfo {
+        let Some(id) = maze.cell_id(position) else {
+            return CellInfo::default();
+        };
+        let g = known(self.gscore[id]);
+        CellInfo {
+            g,
+            h: g.map(|_| Self::heuristic(position.0, position.1, maze.end.0, maze.end.1)),
+            f: known(self.fscore[id]),
+            parent: known(self.came_from[id]).map(|parent| maze.position_of(parent)),
+            visit_order: known(self.visit_order[id]),
+        }
+    }
+}
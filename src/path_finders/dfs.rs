@@ -1,56 +1,106 @@
 use crate::maze::{CellState, Maze};
 
-use super::path_finder_interface::PathFinder;
-use std::collections::{HashSet};
+use super::path_finder_interface::{CellInfo, PathFinder, SolverEvent};
+use super::stats::SolverStats;
+use super::{known, UNSET};
+use std::mem::size_of;
+use std::time::Instant;
 
-pub(crate) struct DFS {
-    stack: Vec<(usize, usize, Vec<(usize,usize)>)>,
+// Each stack entry is a cell id and the id of the cell that pushed it, the path
+// that led there is rebuilt from `came_from` instead of being carried along.
+type StackEntry = (usize, usize);
+
+#[allow(clippy::upper_case_acronyms)]
+pub struct DFS {
+    stack: Vec<StackEntry>,
     path: Option<Vec<(usize,usize)>>,
-    visited: HashSet<(usize,usize)>,
-    traversed_cells: usize,
+    visited: Vec<bool>,
+    // Where each cell was first expanded from, and when.
+    came_from: Vec<usize>,
+    visit_order: Vec<usize>,
+    // Length of the path that first reached each cell.
+    depth: Vec<usize>,
+    expanded: usize,
+    stats: SolverStats,
 }
 
 impl DFS {
     pub fn new(maze: &Maze) -> Self {
+        let cells = maze.cells.len();
         Self {
-            stack: vec![(maze.start.0 as usize, maze.start.1 as usize, vec![(maze.start.0 as usize, maze.start.1 as usize)])],
+            stack: vec![(maze.id_at(maze.start), UNSET)],
             path: None,
-            visited: HashSet::new(),
-            traversed_cells: 0,
+            visited: vec![false; cells],
+            came_from: vec![UNSET; cells],
+            visit_order: vec![UNSET; cells],
+            depth: vec![UNSET; cells],
+            expanded: 0,
+            stats: SolverStats::default(),
         }
     }
-}
 
-impl PathFinder for DFS {
-    fn iterate(&mut self, maze: &mut crate::maze::Maze) {
-        if self.path.is_some() || self.stack.is_empty() {
-            return;
+    fn memory_estimate(&self) -> usize {
+        self.visited.len() * size_of::<bool>()
+            + self.stack.len() * size_of::<StackEntry>()
+            + (self.came_from.len() + self.visit_order.len() + self.depth.len()) * size_of::<usize>()
+    }
+
+    // `last`, then the cells `came_from` leads back through to the start, start first.
+    fn path_to(&self, maze: &Maze, last: usize, mut parent: Option<usize>) -> Vec<(usize, usize)> {
+        let mut path = vec![maze.position_of(last)];
+        while let Some(id) = parent {
+            path.push(maze.position_of(id));
+            parent = known(self.came_from[id]);
         }
-        let (x, y, path) = self.stack.pop().unwrap();
+        path.reverse();
+        path
+    }
+
+    fn step(&mut self, maze: &Maze) -> Vec<SolverEvent> {
+        let (current, parent) = self.stack.pop().unwrap();
+        let (x, y) = maze.position_of(current);
         if maze.get(x,y) == &CellState::END {
-            self.path = Some(path);
-            return;
+            // The end is never expanded, but the inspector still shows where it was reached from.
+            self.came_from[current] = parent;
+            let path = self.path_to(maze, current, known(parent));
+            self.path = Some(path.clone());
+            return vec![SolverEvent::PathFound(path)];
+        }
+        let mut events = vec![SolverEvent::Expanded((x,y))];
+        // A cell can be pushed by several neighbours before it is first popped.
+        if !self.visited[current] {
+            self.visited[current] = true;
+            self.came_from[current] = parent;
+            self.visit_order[current] = self.expanded;
+            self.depth[current] = known(parent).map_or(0, |parent| self.depth[parent] + 1);
+            self.expanded += 1;
+        } else {
+            self.stats.re_expansions += 1;
+        }
+        for next in maze.neighbours((x,y)) {
+            let id = maze.id_at(next);
+            if !self.visited[id] {
+                self.stack.push((id, current));
+                events.push(SolverEvent::FrontierPush(next));
+            }
         }
+        events
+    }
+}
 
-        maze.set(x,y, CellState::EXPLORED);
-        self.visited.insert((x,y));
-        self.traversed_cells += 1;
-        let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)]
-            .into_iter()
-            .map(|(dx,dy)| (x as isize + dx,  y as isize + dy))
-            .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && nx < maze.width as isize && ny < maze.height as isize)
-            .map(|(nx, ny)| (nx as usize, ny as usize))
-            .filter(|&(nx, ny)| maze.get(nx,ny) != &CellState::WALL)
-            .filter(|&(nx, ny)| !self.visited.contains(&(nx,ny)))
-            .collect::<Vec<(usize, usize)>>();
-        for &(nx, ny) in directions.iter() {
-            let mut new_path = path.clone();
-            new_path.push((nx, ny));
-            self.stack.push((nx, ny, new_path ));
+impl PathFinder for DFS {
+    fn iterate(&mut self, maze: &Maze) -> Vec<SolverEvent> {
+        if self.path.is_some() || self.stack.is_empty() {
+            return vec![];
         }
+        let started = Instant::now();
+        let events = self.step(maze);
+        self.stats.record(&events, started.elapsed());
+        self.stats.record_open_set(self.stack.len(), self.memory_estimate());
+        events
     }
 
-    fn get_path(&self, maze: & crate::maze::Maze) -> Vec<(usize,usize)> {
+    fn get_path(&self, _maze: & crate::maze::Maze) -> Vec<(usize,usize)> {
         if let Some(path) = &self.path {
             return path.clone();
         }
@@ -60,16 +110,36 @@ impl PathFinder for DFS {
     fn is_solved(&self) -> bool {
         self.path.is_some()
     }
+
+    fn is_exhausted(&self) -> bool {
+        self.path.is_none() && self.stack.is_empty()
+    }
     
-    fn get_accuracy(&self, maze: &Maze) -> f32  {
-        if let Some(path) = &self.path {
-             path.len() as f32 / self.traversed_cells as f32
-        } else {
-            0.
-        }
+    fn get_stats(&self) -> &SolverStats {
+        &self.stats
     }
     
     fn get_new_solver(&mut self, maze: &Maze) -> Box<dyn PathFinder + Sync + Send> {
         Box::new(DFS::new(maze))
     }
-}
\ No newline at end of file
+
+    fn name(&self) -> &str {
+        "DFS"
+    }
+
+    fn frontier_size(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn cell_info(&self, maze: &Maze, position: (usize, usize)) -> CellInfo {
+        let Some(id) = maze.cell_id(position) else {
+            return CellInfo::default();
+        };
+        CellInfo {
+            g: known(self.depth[id]),
+            parent: known(self.came_from[id]).map(|parent| maze.position_of(parent)),
+            visit_order: known(self.visit_order[id]),
+            ..CellInfo::default()
+        }
+    }
+}
@@ -1,104 +1,134 @@
-use std::collections::HashMap;
+use std::mem::size_of;
+use std::time::Instant;
 use min_max_heap::MinMaxHeap;
 use crate::maze::{CellState, Maze};
-use super::path_finder_interface::PathFinder;
+use super::path_finder_interface::{CellInfo, PathFinder, SolverEvent};
+use super::stats::SolverStats;
+use super::{known, UNSET};
 
-pub(crate) struct Djikstras {
+pub struct Djikstras {
     min_heap: MinMaxHeap<(usize, (usize, usize))>,
-    distances: HashMap<(usize, usize), usize>,
-    solved: bool,
-    traversed_cells: usize,
+    distances: Vec<usize>,
+    came_from: Vec<usize>,
+    // Cells already expanded, later heap entries for them are outdated.
+    closed: Vec<bool>,
+    visit_order: Vec<usize>,
+    expanded: usize,
+    end: Option<usize>,
+    stats: SolverStats,
 }
 
 impl Djikstras{
     pub fn new(maze: &Maze) -> Self {
+        let cells = maze.cells.len();
         let mut min_heap = MinMaxHeap::new();
-        min_heap.push((0, (maze.start.0 as usize, maze.start.1 as usize)));
+        min_heap.push((0, (maze.start.0, maze.start.1)));
+        let mut distances = vec![UNSET; cells];
+        distances[maze.id_at(maze.start)] = 0;
         Self {
             min_heap,
-            distances: HashMap::new(),
-            solved: false,
-            traversed_cells: 0,
+            distances,
+            came_from: vec![UNSET; cells],
+            closed: vec![false; cells],
+            visit_order: vec![UNSET; cells],
+            expanded: 0,
+            end: None,
+            stats: SolverStats::default(),
         }
     }
 
-}
+    fn memory_estimate(&self) -> usize {
+        (self.distances.len() + self.came_from.len() + self.visit_order.len()) * size_of::<usize>()
+            + self.closed.len() * size_of::<bool>()
+            + self.min_heap.len() * size_of::<(usize, (usize, usize))>()
+    }
 
-impl PathFinder for Djikstras {
-    fn iterate(&mut self, maze: &mut Maze) {
-        if self.solved {
-            return;
-        }
-        if self.min_heap.is_empty(){
-            dbg!("No path found");
-            return;
-        }
+    fn step(&mut self, maze: &Maze) -> Vec<SolverEvent> {
         let (dist, (x, y)) = self.min_heap.pop_min().unwrap();
-        let index = y * maze.width + x;
-        if maze.get(x,y) == &CellState::EXPLORED {
-            return;
+        let current = maze.id_at((x,y));
+        if self.closed[current] {
+            return vec![];
         }
         if maze.get(x,y) == &CellState::END {
-            self.solved = true;
-            return;
+            self.end = Some(current);
+            return vec![SolverEvent::PathFound(self.get_path(maze))];
         }
-        self.traversed_cells += 1;
-        maze.set(x, y, CellState::EXPLORED);
-        let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)]
-            .into_iter()
-            .map(|(dx,dy)| (x as isize + dx,  y as isize + dy))
-            .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && nx < maze.width as isize && ny < maze.height as isize)
-            .filter(|&(nx, ny)| maze.cells[ny as usize * maze.width + nx as usize] != CellState::WALL)
-            .map(|(nx, ny)| (nx as usize, ny as usize)).collect::<Vec<(usize, usize)>>();
-        for &(nx, ny) in directions.iter() {
+        let mut events = vec![SolverEvent::Expanded((x,y))];
+        self.visit_order[current] = self.expanded;
+        self.expanded += 1;
+        self.closed[current] = true;
+        for (nx, ny) in maze.neighbours((x,y)) {
+            let next = maze.id_at((nx, ny));
             let next_cost = dist + 1;
-            if self.distances.contains_key(&(nx, ny)) && next_cost >= *self.distances.get(&(nx, ny)).unwrap() {
+            // Unknown distances are `UNSET`, larger than any real one.
+            if next_cost >= self.distances[next] {
                 continue;
             }
-            self.distances.insert((nx, ny), next_cost);
+            self.distances[next] = next_cost;
+            self.came_from[next] = current;
             self.min_heap.push((next_cost, (nx, ny)));
+            events.push(SolverEvent::FrontierPush((nx, ny)));
+        }
+        events
+    }
+}
+
+impl PathFinder for Djikstras {
+    fn iterate(&mut self, maze: &Maze) -> Vec<SolverEvent> {
+        if self.is_solved() || self.min_heap.is_empty() {
+            return vec![];
         }
+        let started = Instant::now();
+        let events = self.step(maze);
+        self.stats.record(&events, started.elapsed());
+        self.stats.record_open_set(self.min_heap.len(), self.memory_estimate());
+        events
     }
 
     fn get_path(&self, maze: &Maze) -> Vec<(usize, usize)> {
-        let maze_width = maze.width;
-        let mut step = (maze.end.0 as usize, maze.end.1 as usize);
-        let start = (maze.start.0 as usize, maze.start.1 as usize);
         let mut path: Vec<(usize,usize)> = vec![];
-        while step != start {
-            path.push((step.0, step.1));
-
-            let directions = [(0, 1), (0, -1), (1, 0), (-1, 0)]
-                .iter()
-                .map(|&(dx, dy)| (step.0 as isize + dx, step.1 as isize + dy))
-                .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && nx < maze_width as isize && ny < maze.cells.len() as isize / maze_width as isize) // Ensure in bounds
-                .map(|(nx, ny)| (nx as usize, ny as usize))
-                .collect::<Vec<(usize, usize)>>();
-
-            let current_distance = self.distances.get(&step).copied().unwrap_or(usize::MAX);
-
-            if let Some(next_step) = directions.iter()
-                .filter_map(|&pos| self.distances.get(&pos).map(|&dist| (dist, pos)))
-                .filter(|&(dist, _)| dist < current_distance) // Ensure we always move to a lower distance
-                .min_by_key(|&(dist, _)| dist) {
-                step = next_step.1;
-            } else {
-                println!("Error: Could not find a valid path back to the start.");
-                break; // Break if no valid reduction in path distance is found
-            }
+        let mut current = self.end;
+        while let Some(id) = current {
+            path.push(maze.position_of(id));
+            current = known(self.came_from[id]);
         }
+        path.reverse();
         path
     }
 
     fn is_solved(&self) -> bool {
-        self.solved
+        self.end.is_some()
+    }
+
+    fn is_exhausted(&self) -> bool {
+        !self.is_solved() && self.min_heap.is_empty()
     }
     
-    fn get_accuracy(&self, maze: &Maze) -> f32  {
-         self.get_path(maze).len() as f32 / self.traversed_cells as f32
+    fn get_stats(&self) -> &SolverStats {
+        &self.stats
     }
     
     fn get_new_solver(&mut self, maze: &Maze) -> Box<dyn PathFinder + Sync + Send> {
         Box::new(Djikstras::new(maze))
     }
-}
\ No newline at end of file
+
+    fn name(&self) -> &str {
+        "Dijkstra"
+    }
+
+    fn frontier_size(&self) -> usize {
+        self.min_heap.len()
+    }
+
+    fn cell_info(&self, maze: &Maze, position: (usize, usize)) -> CellInfo {
+        let Some(id) = maze.cell_id(position) else {
+            return CellInfo::default();
+        };
+        CellInfo {
+            g: known(self.distances[id]),
+            parent: known(self.came_from[id]).map(|parent| maze.position_of(parent)),
+            visit_order: known(self.visit_order[id]),
+            ..CellInfo::default()
+        }
+    }
+}
@@ -1,4 +1,217 @@
 pub mod djikstras;
 pub mod path_finder_interface;
 pub mod dfs;
-pub mod a_star;
\ No newline at end of file
+pub mod a_star;
+pub mod replay;
+pub mod stats;
+
+use crate::maze::Maze;
+use a_star::AStar;
+use dfs::DFS;
+use djikstras::Djikstras;
+use path_finder_interface::PathFinder;
+
+/// A value a solver has not set yet, in its per-cell arrays indexed by `Maze::cell_id`.
+pub(crate) const UNSET: usize = usize::MAX;
+
+/// `value` unless it is `UNSET`.
+pub(crate) fn known(value: usize) -> Option<usize> {
+    (value != UNSET).then_some(value)
+}
+
+/// What a search algorithm guarantees or can handle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Always finds a shortest path.
+    pub optimal: bool,
+    /// Takes cell costs other than 1 into account.
+    pub weighted: bool,
+    /// Moves diagonally as well as straight.
+    pub diagonal: bool,
+}
+
+impl Capabilities {
+    /// Names of the capabilities that are set.
+    pub fn names(&self) -> Vec<&'static str> {
+        [(self.optimal, "optimal"), (self.weighted, "weighted"), (self.diagonal, "diagonal")]
+            .into_iter()
+            .filter_map(|(set, name)| set.then_some(name))
+            .collect()
+    }
+}
+
+/// A search algorithm the front-ends can offer.
+pub struct SolverEntry {
+    /// Name on the command line. The solver's own `name()` is the one shown to the user.
+    pub name: &'static str,
+    pub description: &'static str,
+    pub capabilities: Capabilities,
+    pub create: fn(&Maze) -> Box<dyn PathFinder + Sync + Send>,
+}
+
+/// Every search algorithm, in keybinding order. Menus, options and the benchmark are built from this list,
+/// so a new solver only needs an entry here.
+pub const SOLVERS: &[SolverEntry] = &[
+    SolverEntry {
+        name: "astar",
+        description: "Best first by distance travelled plus distance left",
+        capabilities: Capabilities { optimal: true, weighted: false, diagonal: false },
+        create: |maze| Box::new(AStar::new(maze)),
+    },
+    SolverEntry {
+        name: "dijkstra",
+        description: "Closest to the start first",
+        capabilities: Capabilities { optimal: true, weighted: false, diagonal: false },
+        create: |maze| Box::new(Djikstras::new(maze)),
+    },
+    SolverEntry {
+        name: "dfs",
+        description: "One corridor to its end before the next",
+        capabilities: Capabilities { optimal: false, weighted: false, diagonal: false },
+        create: |maze| Box::new(DFS::new(maze)),
+    },
+];
+
+pub fn find_solver(name: &str) -> Option<&'static SolverEntry> {
+    SOLVERS.iter().find(|entry| entry.name == name)
+}
+
+/// Command-line names of the search algorithms, in keybinding order.
+pub fn solver_names() -> Vec<&'static str> {
+    SOLVERS.iter().map(|entry| entry.name).collect()
+}
+
+pub fn new_solver(name: &str, maze: &Maze) -> Option<Box<dyn PathFinder + Sync + Send>> {
+    find_solver(name).map(|entry| (entry.create)(maze))
+}
+
+/// A fresh instance of every search algorithm, in keybinding order.
+pub fn all_solvers(maze: &Maze) -> Vec<Box<dyn PathFinder + Sync + Send>> {
+    SOLVERS.iter().map(|entry| (entry.create)(maze)).collect()
+}
+
+/// Runs `solver` on `maze` until it is solved or exhausted. Every step is the events of one
+/// `iterate` call, with the frontier size and statistics after it.
+#[cfg(test)]
+pub(crate) fn solve(solver: &mut dyn PathFinder, maze: &Maze) -> Vec<(Vec<path_finder_interface::SolverEvent>, usize, stats::SolverStats)> {
+    let mut steps = vec![];
+    while !solver.is_solved() && !solver.is_exhausted() {
+        let events = solver.iterate(maze);
+        steps.push((events, solver.frontier_size(), solver.get_stats().clone()));
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{generate_maze, CellState, Generator};
+    use std::collections::VecDeque;
+
+    // Steps on the shortest path from start to end, found breadth first.
+    fn shortest_cost(maze: &Maze) -> Option<usize> {
+        let mut distance = vec![None; maze.cells.len()];
+        distance[maze.id_at(maze.start)] = Some(0);
+        let mut queue = VecDeque::from([maze.start]);
+        while let Some(position) = queue.pop_front() {
+            let next_distance = distance[maze.id_at(position)].map(|distance| distance + 1);
+            for next in maze.neighbours(position) {
+                if distance[maze.id_at(next)].is_none() {
+                    distance[maze.id_at(next)] = next_distance;
+                    queue.push_back(next);
+                }
+            }
+        }
+        distance[maze.id_at(maze.end)]
+    }
+
+    // A path from start to end where every cell is next to the one before it.
+    fn assert_walkable(path: &[(usize, usize)], maze: &Maze) {
+        assert_eq!(path.first(), Some(&maze.start));
+        assert_eq!(path.last(), Some(&maze.end));
+        for pair in path.windows(2) {
+            assert!(maze.neighbours(pair[0]).any(|next| next == pair[1]), "{:?} does not lead to {:?}", pair[0], pair[1]);
+        }
+    }
+
+    fn solved(name: &str, maze: &Maze) -> Box<dyn PathFinder + Sync + Send> {
+        let mut solver = new_solver(name, maze).unwrap();
+        solve(solver.as_mut(), maze);
+        assert!(solver.is_solved(), "{} did not solve maze {}", name, maze.seed);
+        assert!(solver.get_stats().accuracy().is_some_and(|accuracy| accuracy <= 1.), "{} on maze {}", name, maze.seed);
+        solver
+    }
+
+    #[test]
+    fn optimal_solvers_find_a_shortest_path() {
+        for seed in 0..10 {
+            let maze = generate_maze(30, 20, Generator::Scatter, seed);
+            let cost = shortest_cost(&maze).unwrap();
+            for entry in SOLVERS.iter().filter(|entry| entry.capabilities.optimal) {
+                let path = solved(entry.name, &maze).get_path(&maze);
+                assert_walkable(&path, &maze);
+                assert_eq!(path.len() - 1, cost, "{} on maze {}", entry.name, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn dfs_finds_a_walkable_path() {
+        for seed in 0..10 {
+            let maze = generate_maze(30, 20, Generator::Scatter, seed);
+            assert_walkable(&solved("dfs", &maze).get_path(&maze), &maze);
+        }
+    }
+
+    #[test]
+    fn walled_off_end_exhausts_every_solver() {
+        use CellState::*;
+        let cells = vec![
+            START, UNEXPLORED, UNEXPLORED, WALL,
+            UNEXPLORED, UNEXPLORED, WALL, WALL,
+            WALL, WALL, WALL, END,
+        ];
+        let maze = Maze::new(4, 3, cells, (0, 0), (3, 2), 0).unwrap();
+        for entry in SOLVERS {
+            let mut solver = (entry.create)(&maze);
+            solve(solver.as_mut(), &maze);
+            assert!(solver.is_exhausted(), "{}", entry.name);
+            assert!(!solver.is_solved(), "{}", entry.name);
+            assert!(solver.get_path(&maze).is_empty(), "{}", entry.name);
+            assert!(solver.iterate(&maze).is_empty(), "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn parents_follow_the_path() {
+        let maze = generate_maze(30, 20, Generator::Scatter, 4);
+        for entry in SOLVERS {
+            let solver = solved(entry.name, &maze);
+            let path = solver.get_path(&maze);
+            assert_eq!(solver.cell_info(&maze, maze.start).parent, None, "{}", entry.name);
+            for pair in path.windows(2) {
+                assert_eq!(solver.cell_info(&maze, pair[1]).parent, Some(pair[0]), "{} at {:?}", entry.name, pair[1]);
+            }
+        }
+    }
+
+    // Orders are handed out in sequence, one per cell expanded for the first time, so the inspector
+    // can tell what was expanded at a rewound step from the statistics of that step.
+    #[test]
+    fn visit_orders_count_distinct_expansions() {
+        let maze = generate_maze(30, 20, Generator::Scatter, 6);
+        let mut dfs = DFS::new(&maze);
+        let steps = solve(&mut dfs, &maze).into_iter().map(|(events, _, _)| events).collect();
+        let trace = crate::trace::Trace::new(&maze, dfs.name(), steps);
+        let mut solvers = all_solvers(&maze);
+        solvers.push(Box::new(replay::Replay::new(&trace)));
+        for mut solver in solvers {
+            solve(solver.as_mut(), &maze);
+            let stats = solver.get_stats();
+            let ordered = (0..maze.cells.len())
+                .filter(|&id| solver.cell_info(&maze, maze.position_of(id)).visit_order.is_some())
+                .count();
+            assert_eq!(ordered, stats.nodes_expanded - stats.re_expansions, "{}", solver.name());
+        }
+    }
+}
@@ -1,9 +1,44 @@
-use crate::Maze;
+use crate::maze::Maze;
+use super::stats::SolverStats;
 
-pub(crate) trait PathFinder: Sync {
-    fn iterate(&mut self, maze: &mut Maze);
+/// Something a solver did during a single `iterate` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolverEvent {
+    Expanded((usize, usize)),
+    FrontierPush((usize, usize)),
+    PathFound(Vec<(usize, usize)>),
+}
+
+/// What a solver knows about one cell, for the inspector. Whatever it does not track is `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CellInfo {
+    /// Cost of the best known route from the start: A*'s g score, Dijkstra's distance, DFS's depth.
+    pub g: Option<usize>,
+    /// Heuristic estimate of the cost to the end.
+    pub h: Option<usize>,
+    /// `g + h`, the order A* expands cells in.
+    pub f: Option<usize>,
+    /// The cell this one was reached from.
+    pub parent: Option<(usize, usize)>,
+    /// Position in the expansion order, 0 for the first cell expanded.
+    pub visit_order: Option<usize>,
+}
+
+pub trait PathFinder: Sync {
+    /// Runs one step of the search. The maze is only read, `Maze::apply_events` shows the returned events on it.
+    /// Returns no events once the solver is solved or exhausted.
+    fn iterate(&mut self, maze: &Maze) -> Vec<SolverEvent>;
+    /// The path found, from start to end with both included. Empty while unsolved.
     fn get_path(&self, maze: & Maze) -> Vec<(usize,usize)>;
     fn is_solved(&self) -> bool;
-    fn get_accuracy(&self, maze: &Maze) -> f32 ;
+    /// Whether the search ran out of cells to expand without reaching the end.
+    fn is_exhausted(&self) -> bool;
+    /// Statistics of the run so far, updated by every `iterate` call.
+    fn get_stats(&self) -> &SolverStats;
     fn get_new_solver(&mut self, maze: &Maze) -> Box<dyn PathFinder + Sync + Send>;
-}
\ No newline at end of file
+    fn name(&self) -> &str;
+    /// Number of cells waiting to be expanded.
+    fn frontier_size(&self) -> usize;
+    /// Per-cell bookkeeping of the solver, for debugging and the inspector.
+    fn cell_info(&self, maze: &Maze, position: (usize, usize)) -> CellInfo;
+}
@@ -0,0 +1,114 @@
+use std::time::Duration;
+use crate::maze::Maze;
+use crate::trace::Trace;
+
+use super::path_finder_interface::{CellInfo, PathFinder, SolverEvent};
+use super::stats::SolverStats;
+use super::{known, UNSET};
+
+/// Plays back a recorded `Trace` one step per `iterate` instead of searching.
+pub struct Replay {
+    algorithm: String,
+    steps: Vec<Vec<SolverEvent>>,
+    next_step: usize,
+    path: Option<Vec<(usize,usize)>>,
+    frontier_size: usize,
+    // Indexed by the cell ids of the trace's maze.
+    visit_order: Vec<usize>,
+    expanded: usize,
+    stats: SolverStats,
+}
+
+impl Replay {
+    pub fn new(trace: &Trace) -> Self {
+        Self {
+            algorithm: trace.algorithm.clone(),
+            steps: trace.steps.clone(),
+            next_step: 0,
+            path: None,
+            frontier_size: 0,
+            visit_order: vec![UNSET; trace.maze.cells.len()],
+            expanded: 0,
+            stats: SolverStats::default(),
+        }
+    }
+}
+
+impl PathFinder for Replay {
+    fn iterate(&mut self, maze: &Maze) -> Vec<SolverEvent> {
+        if self.path.is_some() || self.next_step >= self.steps.len() {
+            return vec![];
+        }
+        let events = self.steps[self.next_step].clone();
+        self.next_step += 1;
+        for event in events.iter() {
+            match event {
+                SolverEvent::Expanded(position) => {
+                    self.frontier_size = self.frontier_size.saturating_sub(1);
+                    // A hand-edited trace may point outside the maze, such events are played but not ordered.
+                    let order = maze.cell_id(*position).and_then(|id| self.visit_order.get_mut(id));
+                    match order {
+                        Some(order) if *order == UNSET => {
+                            *order = self.expanded;
+                            self.expanded += 1;
+                        }
+                        Some(_) => self.stats.re_expansions += 1,
+                        None => {}
+                    }
+                }
+                SolverEvent::FrontierPush(_) => self.frontier_size += 1,
+                SolverEvent::PathFound(path) => self.path = Some(path.clone()),
+            }
+        }
+        // Timings and memory are the replay's own, not the recorded solver's.
+        self.stats.record(&events, Duration::ZERO);
+        self.stats.record_open_set(self.frontier_size, 0);
+        events
+    }
+
+    fn get_path(&self, _maze: &Maze) -> Vec<(usize,usize)> {
+        self.path.clone().unwrap_or_default()
+    }
+
+    fn is_solved(&self) -> bool {
+        self.path.is_some()
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.path.is_none() && self.next_step >= self.steps.len()
+    }
+
+    fn get_stats(&self) -> &SolverStats {
+        &self.stats
+    }
+
+    fn get_new_solver(&mut self, _maze: &Maze) -> Box<dyn PathFinder + Sync + Send> {
+        Box::new(Self {
+            algorithm: self.algorithm.clone(),
+            steps: self.steps.clone(),
+            next_step: 0,
+            path: None,
+            frontier_size: 0,
+            visit_order: vec![UNSET; self.visit_order.len()],
+            expanded: 0,
+            stats: SolverStats::default(),
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.algorithm
+    }
+
+    // Only approximate: the trace does not record entries a solver skipped as stale.
+    fn frontier_size(&self) -> usize {
+        self.frontier_size
+    }
+
+    // A trace only records what was expanded when, not the solver's scores.
+    fn cell_info(&self, maze: &Maze, position: (usize, usize)) -> CellInfo {
+        CellInfo {
+            visit_order: maze.cell_id(position).and_then(|id| self.visit_order.get(id)).copied().and_then(known),
+            ..CellInfo::default()
+        }
+    }
+}
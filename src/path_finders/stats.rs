@@ -0,0 +1,104 @@
+use std::fmt;
+use std::time::Duration;
+
+use super::path_finder_interface::SolverEvent;
+
+/// Running totals of a solver, kept up to date by every `iterate` call.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SolverStats {
+    pub nodes_expanded: usize,
+    /// Cells pushed onto the open set, including cells that were already on it.
+    pub nodes_generated: usize,
+    /// Expansions of a cell that had been expanded before.
+    pub re_expansions: usize,
+    pub peak_open_set: usize,
+    /// Largest size of the solver's own bookkeeping, in bytes. An estimate, allocator overhead is not counted.
+    pub peak_memory: usize,
+    /// Cells on the path, start and end included. `None` until a path is found.
+    pub path_length: Option<usize>,
+    /// Steps from start to end along the path. `None` until a path is found.
+    pub path_cost: Option<usize>,
+    pub iterations: usize,
+    pub elapsed: Duration,
+}
+
+impl SolverStats {
+    /// Adds one `iterate` call that produced `events` and took `elapsed`.
+    pub fn record(&mut self, events: &[SolverEvent], elapsed: Duration) {
+        self.iterations += 1;
+        self.elapsed += elapsed;
+        for event in events {
+            match event {
+                SolverEvent::Expanded(_) => self.nodes_expanded += 1,
+                SolverEvent::FrontierPush(_) => self.nodes_generated += 1,
+                SolverEvent::PathFound(path) => {
+                    self.path_length = Some(path.len());
+                    self.path_cost = Some(path.len().saturating_sub(1));
+                }
+            }
+        }
+    }
+
+    pub fn record_open_set(&mut self, size: usize, memory: usize) {
+        self.peak_open_set = self.peak_open_set.max(size);
+        self.peak_memory = self.peak_memory.max(memory);
+    }
+
+    /// Share of the expanded nodes that lie on the path, 1.0 when nothing off the path was expanded.
+    /// `None` while unsolved.
+    ///
+    /// The end is reached but never expanded, so it counts on neither side: the path cells before
+    /// it are `path_cost`.
+    pub fn accuracy(&self) -> Option<f32> {
+        let path_cost = self.path_cost?;
+        if self.nodes_expanded == 0 {
+            return Some(1.);
+        }
+        Some(path_cost as f32 / self.nodes_expanded as f32)
+    }
+}
+
+impl fmt::Display for SolverStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expanded {} ({} again), generated {}, peak open set {} (~{} KiB)",
+            self.nodes_expanded, self.re_expansions, self.nodes_generated, self.peak_open_set, self.peak_memory.div_ceil(1024))?;
+        if let (Some(length), Some(cost)) = (self.path_length, self.path_cost) {
+            write!(f, ", path {} cells / cost {}", length, cost)?;
+        }
+        write!(f, ", {} iterations in {:.2?}", self.iterations, self.elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_counts_events() {
+        let mut stats = SolverStats::default();
+        stats.record(&[SolverEvent::Expanded((0, 0)), SolverEvent::FrontierPush((1, 0)), SolverEvent::FrontierPush((0, 1))], Duration::from_millis(2));
+        stats.record(&[], Duration::from_millis(1));
+        assert_eq!((stats.nodes_expanded, stats.nodes_generated, stats.iterations), (1, 2, 2));
+        assert_eq!(stats.elapsed, Duration::from_millis(3));
+        assert_eq!((stats.path_length, stats.path_cost, stats.accuracy()), (None, None, None));
+        stats.record(&[SolverEvent::PathFound(vec![(0, 0), (1, 0), (2, 0)])], Duration::ZERO);
+        assert_eq!((stats.path_length, stats.path_cost), (Some(3), Some(2)));
+    }
+
+    #[test]
+    fn accuracy_leaves_out_the_end() {
+        let path = vec![(0, 0), (1, 0), (2, 0)];
+        // Only the cells before the end expanded: as accurate as a search gets.
+        let mut stats = SolverStats::default();
+        stats.record(&[SolverEvent::Expanded((0, 0)), SolverEvent::Expanded((1, 0))], Duration::ZERO);
+        stats.record(&[SolverEvent::PathFound(path.clone())], Duration::ZERO);
+        assert_eq!(stats.accuracy(), Some(1.));
+        // Two more cells off the path halve it.
+        stats.record(&[SolverEvent::Expanded((0, 1)), SolverEvent::Expanded((1, 1))], Duration::ZERO);
+        assert_eq!(stats.accuracy(), Some(0.5));
+        // Start and end on the same cell need no expansions at all.
+        let mut stats = SolverStats::default();
+        stats.record(&[SolverEvent::PathFound(vec![(0, 0)])], Duration::ZERO);
+        assert_eq!(stats.accuracy(), Some(1.));
+    }
+}
@@ -0,0 +1,449 @@
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowResized};
+use crate::cli::{Options, Renderer};
+use crate::maze::{self, Generator, Maze};
+use crate::path_finders::path_finder_interface::PathFinder;
+use crate::path_finders::replay::Replay;
+use crate::path_finders::{new_solver, solver_names, SOLVERS};
+use crate::run::SolverRun;
+use crate::speed::Speed;
+use crate::trace::Trace;
+use crate::visualizer::{self, render_maze, CellAssets, CellSize, MazeView};
+
+const TRACE_FILE: &str = "maze.trace";
+// Time per frame spent showing the steps of a background solve, the rest waits for the next frame.
+const BACKGROUND_FRAME_BUDGET: Duration = Duration::from_millis(8);
+
+/// Keys picking an algorithm, the nth key picks the nth entry of `SOLVERS`.
+pub const ALGORITHM_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5,
+    KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+];
+
+/// Generates a maze, draws it and animates a solver on it.
+///
+/// The plugin brings no window or renderer of its own, add it next to `DefaultPlugins`, ideally with
+/// `ImagePlugin::default_nearest()` so the tiles stay sharp. The maze is centred on the world origin.
+#[derive(Default)]
+pub struct MazeVisualizerPlugin {
+    pub config: MazeVisualizerConfig,
+}
+
+/// Starting configuration of `MazeVisualizerPlugin`, available as a resource once it is added.
+#[derive(Resource, Clone)]
+pub struct MazeVisualizerConfig {
+    /// Width and height in cells, both above 0.
+    pub maze_size: (usize, usize),
+    pub seed: Option<u64>,
+    pub generator: Generator,
+    /// Name of the starting algorithm, one of `SOLVERS`.
+    pub algorithm: String,
+    /// `None` picks one by maze size.
+    pub renderer: Option<Renderer>,
+    pub speed: Speed,
+    pub play: bool,
+    /// Spawn a 2D camera. Turn it off to draw the maze with a camera the app already has.
+    pub spawn_camera: bool,
+    /// Let the keys listed in the readme drive the solver. Without them the app drives it through `Controls`.
+    pub keyboard: bool,
+    /// Smallest cell in pixels when the maze is fitted to the primary window.
+    pub min_cell_size: usize,
+    /// Folder in the assets holding the tile images, empty for the assets folder itself.
+    pub tile_folder: String,
+}
+
+impl Default for MazeVisualizerConfig {
+    fn default() -> Self {
+        Self::from(&Options::default())
+    }
+}
+
+impl From<&Options> for MazeVisualizerConfig {
+    fn from(options: &Options) -> Self {
+        Self {
+            maze_size: options.maze_size,
+            seed: options.seed,
+            generator: options.generator,
+            algorithm: options.algorithm.clone(),
+            renderer: options.renderer,
+            speed: options.speed,
+            play: options.play,
+            spawn_camera: true,
+            keyboard: true,
+            min_cell_size: 4,
+            tile_folder: String::new(),
+        }
+    }
+}
+
+impl MazeVisualizerConfig {
+    fn tile(&self, name: &str) -> String {
+        if self.tile_folder.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", self.tile_folder.trim_end_matches('/'), name)
+        }
+    }
+}
+
+/// The plugin's systems that advance the solver, so an app can pause them with a run condition.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MazeSystems {
+    Solve,
+}
+
+/// What the visualizer is doing. Set `NextState<MazeState>` to `Generating` for a new maze.
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MazeState {
+    /// A mouse button is held down on the maze, the solver restarts on every change.
+    Editing,
+    /// A new maze is made and drawn on entering, then the solver starts or waits from the next frame.
+    /// Asking for another maze during that frame is ignored.
+    #[default]
+    Generating,
+    /// The solver advances every frame, after redoing any rewound steps.
+    Solving,
+    /// Waiting for `Space`, or to be stepped or rewound.
+    Paused,
+    /// The run reached the end, and the timeline is at its last step.
+    Solved,
+    /// The solver ran out of cells to expand without reaching the end.
+    NoPath,
+}
+
+/// The run on the main maze. Its history is rewound with `B` and the timeline and saved with `T`,
+/// `I` starts the background solve. A new solver replaces this one for a new maze or algorithm,
+/// which drops and so cancels the background solve.
+#[derive(Resource, Deref, DerefMut)]
+pub struct Solver(pub SolverRun);
+
+impl Solver {
+    pub fn new(solver: Box<dyn PathFinder + Sync + Send>, maze: &Maze) -> Self {
+        Self(SolverRun::new(solver, maze))
+    }
+
+    /// `Solved` or `NoPath` once the run is over, as long as the history is not rewound.
+    pub fn finished_state(&self) -> Option<MazeState> {
+        if !self.history.is_at_end() {
+            None
+        } else if self.solver.is_solved() {
+            Some(MazeState::Solved)
+        } else if self.solver.is_exhausted() {
+            Some(MazeState::NoPath)
+        } else {
+            None
+        }
+    }
+}
+
+/// A trace to play back instead of running a solver, insert it before adding the plugin.
+/// Resets restore its maze instead of generating one.
+#[derive(Resource)]
+pub struct LoadedTrace(pub Trace);
+
+#[derive(Resource)]
+pub struct Controls {
+    pub maze_size: (usize, usize),
+    pub generator: Generator,
+    /// Seed for the next generated maze, random once it has been used.
+    pub seed: Option<u64>,
+    pub speed: Speed,
+    /// Part of an iteration owed by a per-second speed, run once it adds up to a whole one.
+    pub carry: f32,
+}
+
+impl Controls {
+    fn generate_maze(&mut self) -> Maze {
+        let seed = self.seed.take().unwrap_or_else(rand::random);
+        maze::generate_maze(self.maze_size.0, self.maze_size.1, self.generator, seed)
+    }
+}
+
+/// Last thing the visualizer wants to tell the user, until it is replaced.
+#[derive(Resource, Default)]
+pub struct StatusMessage(pub String);
+
+impl Plugin for MazeVisualizerPlugin {
+    fn build(&self, app: &mut App) {
+        let config = &self.config;
+        if !SOLVERS.iter().any(|entry| entry.name == config.algorithm) {
+            panic!("Unknown algorithm `{}`, expected one of {}", config.algorithm, solver_names().join(", "));
+        }
+        let maze_size = app.world.get_resource::<LoadedTrace>()
+            .map_or(config.maze_size, |trace| (trace.0.maze.width, trace.0.maze.height));
+        if maze_size.0 == 0 || maze_size.1 == 0 {
+            panic!("Maze size {}x{} has no cells, both sides must be above 0", maze_size.0, maze_size.1);
+        }
+        app
+            .insert_resource(Controls {
+                maze_size,
+                generator: config.generator,
+                seed: config.seed,
+                speed: config.speed,
+                carry: 0.,
+            })
+            .insert_resource(config.clone())
+            .init_resource::<StatusMessage>()
+            .init_resource::<MazeView>()
+            .init_state::<MazeState>()
+            .add_systems(Startup, (spawn_camera.run_if(|config: Res<MazeVisualizerConfig>| config.spawn_camera), setup_assets))
+            // Chained, so the maze is drawn once the new resources are in.
+            .add_systems(OnEnter(MazeState::Generating), (generate_maze, render_maze).chain())
+            .add_systems(Update, (toggle_solve, change_speed, change_algorithm, save_trace).run_if(keyboard_enabled))
+            // `Generating` already has a new maze on its way.
+            .add_systems(Update, (change_maze_size, new_maze).run_if(keyboard_enabled).run_if(not(in_state(MazeState::Generating))))
+            .add_systems(OnEnter(MazeState::Solved), announce_solved)
+            .add_systems(OnEnter(MazeState::NoPath), announce_no_path)
+            .add_systems(PostUpdate, leave_generating.run_if(in_state(MazeState::Generating)))
+            .add_systems(Update, (
+                run_solver.run_if(in_state(MazeState::Solving)).run_if(no_background),
+                step_solver.after(run_solver).run_if(keyboard_enabled),
+                finish_run.after(step_solver).after(apply_background),
+            ).in_set(MazeSystems::Solve))
+            .add_systems(Update, apply_background.after(step_solver))
+            .add_systems(Update, visualizer::update_maze.after(apply_background))
+            .add_systems(Update, (
+                fit_to_window,
+                visualizer::fit_maze.after(fit_to_window).run_if(resource_changed::<CellSize>),
+            ));
+    }
+}
+
+// Entering `Generating` asked for the state that follows it. Bevy skips a transition to the current
+// state, so if something replaced that request with `Generating` the app would stay here for good.
+fn leave_generating(mut next_state: ResMut<NextState<MazeState>>) {
+    if matches!(next_state.0, None | Some(MazeState::Generating)) {
+        next_state.set(MazeState::Paused);
+    }
+}
+
+fn keyboard_enabled(config: Res<MazeVisualizerConfig>) -> bool {
+    config.keyboard
+}
+
+fn toggle_solve(state: Res<State<MazeState>>, mut next_state: ResMut<NextState<MazeState>>, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    if !keyboard_input.just_pressed(KeyCode::Space) {
+        return;
+    }
+    match state.get() {
+        MazeState::Solving => next_state.set(MazeState::Paused),
+        MazeState::Paused => next_state.set(MazeState::Solving),
+        _ => {}
+    }
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn setup_assets(mut commands: Commands, asset_server: Res<AssetServer>, config: Res<MazeVisualizerConfig>) {
+    let assets = CellAssets {
+        start_tile: asset_server.load(config.tile("start_tile.png")),
+        end_tile: asset_server.load(config.tile("end_tile.png")),
+        wall_tile: asset_server.load(config.tile("wall_tile.png")),
+        unexplored_tile: asset_server.load(config.tile("unexplored_tile.png")),
+        explored_tile: asset_server.load(config.tile("explored_tile.png")),
+        frontier_tile: asset_server.load(config.tile("frontier_tile.png")),
+        path_tile: asset_server.load(config.tile("path_tile.png")),
+    };
+    commands.insert_resource(assets);
+}
+
+fn new_maze(mut next_state: ResMut<NextState<MazeState>>, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        next_state.set(MazeState::Generating);
+    }
+}
+
+pub fn change_algorithm(
+    mut solver: ResMut<Solver>,
+    mut maze: ResMut<Maze>,
+    mut next_state: ResMut<NextState<MazeState>>,
+    mut message: ResMut<StatusMessage>,
+    keyboard_input: Res<ButtonInput<KeyCode>>) {
+
+    for (key, entry) in ALGORITHM_KEYS.into_iter().zip(SOLVERS) {
+        if !keyboard_input.just_pressed(key) {
+            continue;
+        }
+        maze.reset_explored_paths();
+        *solver = Solver::new((entry.create)(&maze), &maze);
+        next_state.set(MazeState::Paused);
+        message.0 = format!("Changing to {}", solver.solver.name());
+        info!("{}", message.0);
+    }
+}
+
+fn save_trace(solver: Res<Solver>, maze: Res<Maze>, mut message: ResMut<StatusMessage>, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    if !keyboard_input.just_pressed(KeyCode::KeyT) {
+        return;
+    }
+    let trace = Trace::new(&maze, solver.solver.name(), solver.history.events());
+    message.0 = match trace.save(TRACE_FILE) {
+        Ok(()) => format!("Saved {} steps of {} to {}", trace.steps.len(), trace.algorithm, TRACE_FILE),
+        Err(err) => format!("Could not save trace: {}", err),
+    };
+    info!("{}", message.0);
+}
+
+fn change_speed(mut controls: ResMut<Controls>, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    if keyboard_input.any_just_pressed([KeyCode::Equal, KeyCode::NumpadAdd]) {
+        controls.speed = controls.speed.faster();
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+        controls.speed = controls.speed.slower();
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyM) {
+        controls.speed = controls.speed.toggle_unit();
+        controls.carry = 0.;
+    }
+}
+
+fn change_maze_size(mut controls: ResMut<Controls>, mut next_state: ResMut<NextState<MazeState>>, keyboard_input: Res<ButtonInput<KeyCode>>){
+    let (width, height) = controls.maze_size;
+    if keyboard_input.just_pressed(KeyCode::ArrowDown){
+        controls.maze_size.1 += 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) && controls.maze_size.1 > 1 {
+        controls.maze_size.1 -= 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) && controls.maze_size.0 > 1{
+        controls.maze_size.0 -= 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        controls.maze_size.0 += 1;
+    }
+    if controls.maze_size != (width, height) {
+        next_state.set(MazeState::Generating);
+    }
+}
+
+/// Makes the maze for `Generating`, or restores the replayed one, with a new solver for it. The first
+/// maze starts solving if the config says so, the others wait.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_maze(
+    mut commands: Commands,
+    mut controls: ResMut<Controls>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    solver: Option<ResMut<Solver>>,
+    mut message: ResMut<StatusMessage>,
+    mut next_state: ResMut<NextState<MazeState>>,
+    config: Res<MazeVisualizerConfig>,
+    loaded_trace: Option<Res<LoadedTrace>>) {
+
+    message.0.clear();
+    let maze = match &loaded_trace {
+        Some(trace) => trace.0.maze.clone(),
+        None => controls.generate_maze(),
+    };
+    let (solver, play): (Box<dyn PathFinder + Sync + Send>, bool) = match (solver, loaded_trace) {
+        (Some(mut solver), _) => (solver.solver.get_new_solver(&maze), false),
+        (None, Some(trace)) => (Box::new(Replay::new(&trace.0)), config.play),
+        (None, None) => {
+            let solver = new_solver(&config.algorithm, &maze).expect("the plugin checks the algorithm name when it is built");
+            (solver, config.play)
+        }
+    };
+    commands.insert_resource(CellSize(get_cell_size(window_query.get_single().ok(), &maze, &config)));
+    commands.insert_resource(Solver::new(solver, &maze));
+    commands.insert_resource(maze);
+    next_state.set(if play { MazeState::Solving } else { MazeState::Paused });
+}
+
+// The largest cell size that fits the whole maze in the window, the space left over borders it evenly.
+// Without a primary window the cells get the smallest size.
+fn get_cell_size(window: Option<&Window>, maze: &Maze, config: &MazeVisualizerConfig) -> usize {
+    let Some(window) = window else {
+        return config.min_cell_size;
+    };
+    let cell_size = (window.width() as usize / maze.width).min(window.height() as usize / maze.height);
+    cell_size.max(config.min_cell_size)
+}
+
+fn fit_to_window(
+    mut resized: EventReader<WindowResized>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    maze: Res<Maze>,
+    config: Res<MazeVisualizerConfig>,
+    mut cell_size: ResMut<CellSize>) {
+
+    if resized.read().count() == 0 {
+        return;
+    }
+    let fitted = get_cell_size(window_query.get_single().ok(), &maze, &config);
+    if cell_size.0 != fitted {
+        cell_size.0 = fitted;
+    }
+}
+
+pub fn run_solver(
+    mut solver: ResMut<Solver>,
+    mut maze: ResMut<Maze>,
+    mut controls: ResMut<Controls>,
+    time: Res<Time>) {
+
+    let speed = controls.speed;
+    let iterations = speed.iterations(time.delta(), &mut controls.carry);
+    solver.advance(&mut maze, iterations);
+}
+
+/// `S` and `B` move a paused solver one iteration forward or back, `I` runs it to the end on a
+/// worker thread, or stops it there.
+pub fn step_solver(
+    mut solver: ResMut<Solver>,
+    mut maze: ResMut<Maze>,
+    state: Res<State<MazeState>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>) {
+
+    if let Some(background) = &solver.background {
+        if keyboard_input.just_pressed(KeyCode::KeyI) {
+            background.cancel();
+        }
+        return;
+    }
+    let paused = matches!(state.get(), MazeState::Paused | MazeState::Solved | MazeState::NoPath);
+    if keyboard_input.just_pressed(KeyCode::KeyS) && paused {
+        solver.advance(&mut maze, 1);
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyB) && paused {
+        solver.step_back(&mut maze);
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyI) {
+        solver.solve_in_background(&mut maze);
+    }
+}
+
+/// Shows what the background solve did since the last frame, and takes the solver back once it is done.
+pub fn apply_background(mut solver: ResMut<Solver>, mut maze: ResMut<Maze>) {
+    if solver.background.is_some() {
+        solver.apply_background(&mut maze, BACKGROUND_FRAME_BUDGET);
+    }
+}
+
+fn no_background(solver: Res<Solver>) -> bool {
+    solver.background.is_none()
+}
+
+// Reaching the end of a run finishes it, rewinding a finished run pauses it. A state another
+// system asked for this frame goes first.
+fn finish_run(solver: Res<Solver>, state: Res<State<MazeState>>, mut next_state: ResMut<NextState<MazeState>>) {
+    if solver.background.is_some() || next_state.0.is_some() {
+        return;
+    }
+    match (state.get(), solver.finished_state()) {
+        (MazeState::Solving | MazeState::Paused, Some(finished)) => next_state.set(finished),
+        (MazeState::Solved | MazeState::NoPath, None) => next_state.set(MazeState::Paused),
+        _ => {}
+    }
+}
+
+fn announce_solved(solver: Res<Solver>, mut message: ResMut<StatusMessage>) {
+    message.0 = "Solved!".to_string();
+    info!("Solved! {}", solver.stats());
+}
+
+fn announce_no_path(solver: Res<Solver>, mut message: ResMut<StatusMessage>) {
+    message.0 = "No path, the end cannot be reached".to_string();
+    info!("No path! {}", solver.stats());
+}
@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy::window::WindowResized;
+use mazes::maze::Maze;
+use mazes::path_finders::SOLVERS;
+use mazes::path_finders::path_finder_interface::PathFinder;
+use mazes::visualizer::{self, ChunkedImage, MainMaze};
+use mazes::plugin::{Controls, MazeState};
+
+const MAX_LANES: usize = 4;
+// Room above each panel for its label.
+const LABEL_HEIGHT: f32 = 48.;
+const PANEL_MARGIN: f32 = 8.;
+
+/// One algorithm in a race, on its own copy of the maze.
+struct Lane {
+    maze: Maze,
+    solver: Box<dyn PathFinder + Sync + Send>,
+    // Iterations it took to find the path or give up, `None` while still running.
+    finished_after: Option<usize>,
+    image: ChunkedImage,
+}
+
+/// Split view running several algorithms on the same maze side by side. Only present while racing.
+#[derive(Resource)]
+pub struct Race {
+    lanes: Vec<Lane>,
+}
+
+#[derive(Component)]
+pub struct RaceEntity;
+
+#[derive(Component)]
+pub struct LaneLabel(usize);
+
+pub fn not_racing(race: Option<Res<Race>>) -> bool {
+    race.is_none()
+}
+
+fn ordinal(place: usize) -> String {
+    let suffix = match place {
+        1 => "st",
+        2 => "nd",
+        3 => "rd",
+        _ => "th",
+    };
+    format!("{}{}", place, suffix)
+}
+
+// Two panels sit side by side, three or four in a 2x2 grid. Returns each panel's bottom-left corner and size in world space.
+fn panels(count: usize, window: &Window) -> Vec<(Vec2, Vec2)> {
+    let columns = if count == 1 { 1 } else { 2 };
+    let rows = count.div_ceil(columns);
+    let size = Vec2::new(window.width() / columns as f32, window.height() / rows as f32);
+    (0..count)
+        .map(|index| {
+            let column = index % columns;
+            let row = index / columns;
+            let corner = Vec2::new(
+                column as f32 * size.x - window.width() / 2.,
+                window.height() / 2. - (row + 1) as f32 * size.y,
+            );
+            (corner, size)
+        })
+        .collect()
+}
+
+fn spawn_race(commands: &mut Commands, race: &Race, window: &Window) {
+    for (lane_index, (lane, (corner, size))) in race.lanes.iter().zip(panels(race.lanes.len(), window)).enumerate() {
+        let maze = &lane.maze;
+        let area = size - Vec2::new(2. * PANEL_MARGIN, LABEL_HEIGHT + 2. * PANEL_MARGIN);
+        let cell_size = (area.x / maze.width as f32).min(area.y / maze.height as f32);
+        let origin = corner + Vec2::splat(PANEL_MARGIN) + Vec2::splat(cell_size / 2.);
+        for sprite in lane.image.sprites(origin, cell_size) {
+            commands.spawn((sprite, RaceEntity));
+        }
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section("", TextStyle { font_size: 16., color: Color::WHITE, ..default() }),
+                text_anchor: Anchor::TopLeft,
+                transform: Transform::from_xyz(corner.x + PANEL_MARGIN, corner.y + size.y - PANEL_MARGIN, 1.),
+                ..default()
+            },
+            LaneLabel(lane_index),
+            RaceEntity,
+        ));
+    }
+}
+
+fn show_main_maze(cells: &mut Query<&mut Visibility, MainMaze>, visible: bool) {
+    for mut visibility in cells.iter_mut() {
+        *visibility = if visible { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// `C` starts a race of every algorithm on the current maze, or ends it.
+#[allow(clippy::too_many_arguments)]
+pub fn toggle_race(
+    mut commands: Commands,
+    race: Option<Res<Race>>,
+    maze: Res<Maze>,
+    mut images: ResMut<Assets<Image>>,
+    window_query: Query<&Window>,
+    mut cells: Query<&mut Visibility, MainMaze>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+    race_entities: Query<Entity, With<RaceEntity>>,
+    state: Res<State<MazeState>>,
+    mut next_state: ResMut<NextState<MazeState>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>) {
+
+    if !keyboard_input.just_pressed(KeyCode::KeyC) {
+        return;
+    }
+    if race.is_some() {
+        end_race(commands, cells, race_entities);
+        return;
+    }
+    let mut start = maze.clone();
+    start.reset_explored_paths();
+    // The images start out showing the maze as it is.
+    start.take_changes();
+    let image = |images: &mut Assets<Image>| ChunkedImage::new(&start, images);
+    let lanes = SOLVERS
+        .iter()
+        .take(MAX_LANES)
+        .map(|entry| (entry.create)(&start))
+        .map(|solver| Lane { maze: start.clone(), solver, finished_after: None, image: image(&mut images) })
+        .collect::<Vec<Lane>>();
+    let race = Race { lanes };
+    // The panels are laid out over the window, so the view starts unzoomed.
+    let (mut transform, mut projection) = camera_query.single_mut();
+    transform.translation.x = 0.;
+    transform.translation.y = 0.;
+    projection.scale = 1.;
+    spawn_race(&mut commands, &race, window_query.single());
+    show_main_maze(&mut cells, false);
+    commands.insert_resource(race);
+    // The race plays and pauses with `Space` like a run, a finished run must not hold it up.
+    if matches!(state.get(), MazeState::Solved | MazeState::NoPath) {
+        next_state.set(MazeState::Paused);
+    }
+}
+
+pub fn end_race(
+    mut commands: Commands,
+    mut cells: Query<&mut Visibility, MainMaze>,
+    race_entities: Query<Entity, With<RaceEntity>>) {
+
+    for entity in race_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+    show_main_maze(&mut cells, true);
+    commands.remove_resource::<Race>();
+}
+
+// The panels are laid out over the window, so a new window size lays them out again.
+pub fn fit_race(
+    mut commands: Commands,
+    mut resized: EventReader<WindowResized>,
+    race: Res<Race>,
+    window_query: Query<&Window>,
+    race_entities: Query<Entity, With<RaceEntity>>) {
+
+    if resized.read().count() == 0 {
+        return;
+    }
+    for entity in race_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+    spawn_race(&mut commands, &race, window_query.single());
+}
+
+// Every lane gets the same number of iterations per frame, so the finishing order is fair.
+pub fn run_race(mut race: ResMut<Race>, mut controls: ResMut<Controls>, state: Res<State<MazeState>>, time: Res<Time>) {
+    if *state.get() != MazeState::Solving {
+        return;
+    }
+    let speed = controls.speed;
+    let iterations = speed.iterations(time.delta(), &mut controls.carry);
+    for lane in race.lanes.iter_mut().filter(|lane| lane.finished_after.is_none()) {
+        for _ in 0..iterations {
+            let events = lane.solver.iterate(&lane.maze);
+            lane.maze.apply_events(&events);
+            if lane.solver.is_solved() || lane.solver.is_exhausted() {
+                lane.finished_after = Some(lane.solver.get_stats().iterations);
+                break;
+            }
+        }
+    }
+}
+
+pub fn draw_race(
+    mut race: ResMut<Race>,
+    mut images: ResMut<Assets<Image>>,
+    mut labels: Query<(&LaneLabel, &mut Text)>) {
+
+    for lane in race.lanes.iter_mut() {
+        let changed = lane.maze.take_changes().indices(&lane.maze);
+        if !changed.is_empty() {
+            lane.image.paint(&mut images, changed, |index| visualizer::get_color(&lane.maze.cells[index]));
+        }
+    }
+    for (label, mut text) in labels.iter_mut() {
+        let lane = &race.lanes[label.0];
+        let stats = lane.solver.get_stats();
+        let result = match lane.finished_after {
+            Some(iterations) if lane.solver.is_solved() => {
+                let place = 1 + race
+                    .lanes
+                    .iter()
+                    .filter(|other| other.solver.is_solved() && other.finished_after.is_some_and(|other| other < iterations))
+                    .count();
+                format!("{}: {}", ordinal(place), lane.solver.name())
+            }
+            Some(_) => format!("{} (no path)", lane.solver.name()),
+            None => format!("{} (running)", lane.solver.name()),
+        };
+        let path = stats.path_length.map_or(String::new(), |length| format!(", path {}", length));
+        text.sections[0].value = format!(
+            "{}\n{} iterations, expanded {}, open {}{}",
+            result, stats.iterations, stats.nodes_expanded, lane.solver.frontier_size(), path);
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::{Duration, Instant};
+use crate::background::BackgroundSolve;
+use crate::history::History;
+use crate::maze::Maze;
+use crate::path_finders::path_finder_interface::PathFinder;
+use crate::path_finders::stats::SolverStats;
+
+/// A solver with the history of its steps, run a few steps at a time or on a worker thread.
+///
+/// This is the part of a run the visualizer and the terminal front-end share, they only
+/// decide how many steps to take and what to show for them.
+pub struct SolverRun {
+    /// A fresh stand-in while `background` has the real one.
+    pub solver: Box<dyn PathFinder + Sync + Send>,
+    /// Every `iterate` call since the solver was created.
+    pub history: History,
+    /// The solve running to the end on a worker thread. Dropping it cancels it.
+    pub background: Option<BackgroundSolve>,
+}
+
+impl SolverRun {
+    pub fn new(solver: Box<dyn PathFinder + Sync + Send>, maze: &Maze) -> Self {
+        Self {
+            history: History::new(maze, solver.frontier_size()),
+            solver,
+            background: None,
+        }
+    }
+
+    /// Statistics as of the step at the history cursor.
+    pub fn stats(&self) -> &SolverStats {
+        self.history.stats()
+    }
+
+    /// Runs up to `iterations` solver steps, redoing rewound steps from the history first.
+    /// Returns whether one of them found the path.
+    pub fn advance(&mut self, maze: &mut Maze, iterations: usize) -> bool {
+        let mut solved = false;
+        for _ in 0..iterations {
+            if !self.history.is_at_end() {
+                let next = self.history.cursor() + 1;
+                self.history.seek(next, maze);
+                continue;
+            }
+            if self.solver.is_solved() || self.solver.is_exhausted() {
+                break;
+            }
+            let events = self.solver.iterate(maze);
+            maze.apply_events(&events);
+            solved = self.solver.is_solved();
+            self.history.record(events, self.solver.frontier_size(), self.solver.get_stats().clone(), maze);
+        }
+        solved
+    }
+
+    /// Undoes the newest applied step.
+    pub fn step_back(&mut self, maze: &mut Maze) {
+        let previous = self.history.cursor().saturating_sub(1);
+        self.history.seek(previous, maze);
+    }
+
+    /// Redoes every recorded step, then hands the solver to a worker thread unless it is already done.
+    pub fn solve_in_background(&mut self, maze: &mut Maze) {
+        let end = self.history.len();
+        self.history.seek(end, maze);
+        if !self.solver.is_solved() {
+            let stand_in = self.solver.get_new_solver(maze);
+            let running = std::mem::replace(&mut self.solver, stand_in);
+            self.background = Some(BackgroundSolve::start(running, maze.clone()));
+        }
+    }
+
+    /// Applies and records the steps the background solve took, for up to `budget`, and takes the
+    /// solver back once the worker is done. Returns whether it came back with the path.
+    pub fn apply_background(&mut self, maze: &mut Maze, budget: Duration) -> bool {
+        let Some(background) = self.background.as_mut() else {
+            return false;
+        };
+        let started = Instant::now();
+        while started.elapsed() < budget {
+            let Some((events, frontier_size, stats)) = background.next_step() else {
+                break;
+            };
+            maze.apply_events(&events);
+            self.history.record(events, frontier_size, stats, maze);
+        }
+        if !background.is_finished() {
+            return false;
+        }
+        if let Some(background) = self.background.take() {
+            self.solver = background.finish();
+        }
+        self.solver.is_solved()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{generate_maze, Generator};
+    use crate::path_finders::dfs::DFS;
+
+    #[test]
+    fn advance_redoes_rewound_steps_first() {
+        let mut maze = generate_maze(15, 10, Generator::Scatter, 3);
+        let mut run = SolverRun::new(Box::new(DFS::new(&maze)), &maze);
+        run.advance(&mut maze, 10);
+        let cells = maze.cells.clone();
+        for _ in 0..3 {
+            run.step_back(&mut maze);
+        }
+        assert_eq!(run.history.cursor(), 7);
+
+        // The rewound steps come back from the history without touching the solver.
+        run.advance(&mut maze, 3);
+        assert_eq!(run.history.cursor(), 10);
+        assert_eq!(run.history.len(), 10);
+        assert_eq!(run.solver.get_stats().iterations, 10);
+        assert_eq!(maze.cells, cells);
+        run.advance(&mut maze, 2);
+        assert_eq!(run.history.len(), 12);
+        assert_eq!(run.solver.get_stats().iterations, 12);
+        assert_eq!(run.stats().iterations, 12);
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+use std::time::Duration;
+
+/// How fast the front-ends advance a running solver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Speed {
+    /// A fixed number of iterations every frame, so the pace follows the frame rate.
+    PerFrame(usize),
+    /// A fixed number of iterations per second, whatever the frame rate.
+    PerSecond(usize),
+}
+
+impl Speed {
+    /// Parses `N` as iterations per frame and `N/s` as iterations per second.
+    pub fn parse(value: &str) -> Option<Speed> {
+        let speed = match value.strip_suffix("/s") {
+            Some(rate) => Speed::PerSecond(rate.parse().ok()?),
+            None => Speed::PerFrame(value.parse().ok()?),
+        };
+        (speed.count() > 0).then_some(speed)
+    }
+
+    fn count(self) -> usize {
+        match self {
+            Speed::PerFrame(count) | Speed::PerSecond(count) => count,
+        }
+    }
+
+    fn with_count(self, count: usize) -> Speed {
+        match self {
+            Speed::PerFrame(_) => Speed::PerFrame(count),
+            Speed::PerSecond(_) => Speed::PerSecond(count),
+        }
+    }
+
+    pub fn faster(self) -> Speed {
+        self.with_count(self.count().saturating_mul(2))
+    }
+
+    pub fn slower(self) -> Speed {
+        self.with_count((self.count() / 2).max(1))
+    }
+
+    /// Switches between per frame and per second, keeping the number.
+    pub fn toggle_unit(self) -> Speed {
+        match self {
+            Speed::PerFrame(count) => Speed::PerSecond(count),
+            Speed::PerSecond(count) => Speed::PerFrame(count),
+        }
+    }
+
+    /// Iterations to run for a frame that took `delta`. `carry` keeps the fraction of an
+    /// iteration left over by slow rates until the next frame.
+    pub fn iterations(self, delta: Duration, carry: &mut f32) -> usize {
+        match self {
+            Speed::PerFrame(count) => count,
+            Speed::PerSecond(count) => {
+                *carry += count as f32 * delta.as_secs_f32();
+                let whole = carry.floor();
+                *carry -= whole;
+                whole as usize
+            }
+        }
+    }
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Speed::PerFrame(count) => write!(f, "{} per frame", count),
+            Speed::PerSecond(count) => write!(f, "{} per second", count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_both_units() {
+        assert_eq!(Speed::parse("3"), Some(Speed::PerFrame(3)));
+        assert_eq!(Speed::parse("60/s"), Some(Speed::PerSecond(60)));
+    }
+
+    #[test]
+    fn rejects_invalid_speeds() {
+        for value in ["", "0", "0/s", "-1", "/s", "fast", "2/m", "1.5", "3/s/s"] {
+            assert_eq!(Speed::parse(value), None, "parsed `{}`", value);
+        }
+    }
+
+    #[test]
+    fn per_second_carries_fractions_over() {
+        let mut carry = 0.;
+        let frame = Duration::from_millis(100);
+        let iterations = (0..10).map(|_| Speed::PerSecond(5).iterations(frame, &mut carry)).sum::<usize>();
+        assert_eq!(iterations, 5);
+        assert_eq!(Speed::PerFrame(4).iterations(frame, &mut carry), 4);
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use mazes::maze::Maze;
+use mazes::plugin::{MazeState, Solver};
+
+/// The bar along the bottom of the window, dragging on it scrubs through the solver history.
+#[derive(Component)]
+pub struct Timeline;
+
+/// The part of the bar up to the step the maze shows.
+#[derive(Component)]
+pub struct TimelineFill;
+
+pub fn setup_timeline(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(8.),
+                    left: Val::Px(8.),
+                    right: Val::Px(8.),
+                    height: Val::Px(14.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.75).into(),
+                ..default()
+            },
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            Timeline,
+        ))
+        .with_children(|bar| {
+            bar.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    background_color: Color::rgb(0.3, 0.6, 1.).into(),
+                    ..default()
+                },
+                TimelineFill,
+            ));
+        });
+}
+
+// Pressing or dragging on the bar pauses the solver and moves the maze to that step.
+pub fn scrub_timeline(
+    query: Query<(&Interaction, &RelativeCursorPosition), With<Timeline>>,
+    mut solver: ResMut<Solver>,
+    mut maze: ResMut<Maze>,
+    state: Res<State<MazeState>>,
+    mut next_state: ResMut<NextState<MazeState>>) {
+
+    // The history only grows at its end while a background solve fills it.
+    if solver.background.is_some() {
+        return;
+    }
+    for (interaction, cursor) in query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+        if *state.get() == MazeState::Solving {
+            next_state.set(MazeState::Paused);
+        }
+        let target = (position.x.clamp(0., 1.) * solver.history.len() as f32).round() as usize;
+        if target != solver.history.cursor() {
+            solver.history.seek(target, &mut maze);
+        }
+    }
+}
+
+pub fn update_timeline(mut query: Query<&mut Style, With<TimelineFill>>, solver: Res<Solver>) {
+    let history = &solver.history;
+    let filled = if history.is_empty() {
+        0.
+    } else {
+        history.cursor() as f32 / history.len() as f32 * 100.
+    };
+    for mut style in query.iter_mut() {
+        style.width = Val::Percent(filled);
+    }
+}
@@ -0,0 +1,321 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::maze::{CellState, Maze};
+use crate::path_finders::path_finder_interface::SolverEvent;
+
+// A trace is plain text so two runs can be compared with an ordinary `diff`:
+//
+//   mazes-trace 1
+//   algorithm A*
+//   seed 1234
+//   size 5 3
+//   start 0 0
+//   end 4 2
+//   maze
+//   S.#..
+//   #.#.#
+//   ....E
+//   steps 2
+//   expand 0,0; push 1,0
+//   expand 1,0; push 1,1
+//
+// Every line after `steps` is one `iterate` call, with its events separated by `;`.
+// A path event lists the whole path: `path 0,0 1,0 1,1`.
+
+const HEADER: &str = "mazes-trace 1";
+
+pub struct Trace {
+    pub maze: Maze,
+    pub algorithm: String,
+    pub seed: u64,
+    pub steps: Vec<Vec<SolverEvent>>,
+}
+
+#[derive(Debug)]
+pub enum TraceError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceError::Io(err) => write!(f, "{}", err),
+            TraceError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl From<std::io::Error> for TraceError {
+    fn from(err: std::io::Error) -> Self {
+        TraceError::Io(err)
+    }
+}
+
+impl Trace {
+    /// Records a run on `maze`. Explored and path cells are stored as open cells,
+    /// the steps are what put them back.
+    pub fn new(maze: &Maze, algorithm: &str, steps: Vec<Vec<SolverEvent>>) -> Self {
+        let mut maze = maze.clone();
+        maze.reset_explored_paths();
+        Self {
+            seed: maze.seed,
+            maze,
+            algorithm: algorithm.to_string(),
+            steps,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TraceError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TraceError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    pub fn parse(text: &str) -> Result<Self, TraceError> {
+        let mut lines = Lines { lines: text.lines().enumerate(), current: 0 };
+        if lines.next()? != HEADER {
+            return Err(lines.error(format!("expected `{}`", HEADER)));
+        }
+        let algorithm = lines.field("algorithm")?.to_string();
+        let seed = lines.field("seed")?.parse::<u64>().map_err(|err| lines.error(err.to_string()))?;
+        let (width, height) = lines.point("size")?;
+        let start = lines.point("start")?;
+        let end = lines.point("end")?;
+        if width == 0 || height == 0 || start.0 >= width || start.1 >= height || end.0 >= width || end.1 >= height {
+            return Err(lines.error("start and end must lie inside the maze".to_string()));
+        }
+        if width.checked_mul(height).is_none() {
+            return Err(lines.error("the maze has too many cells".to_string()));
+        }
+        if lines.next()? != "maze" {
+            return Err(lines.error("expected `maze`".to_string()));
+        }
+        // The sizes and counts are untrusted, the vectors grow with what is actually there.
+        let mut cells = vec![];
+        for y in 0..height {
+            let row = lines.next()?;
+            if row.chars().count() != width {
+                return Err(lines.error(format!("expected a row of {} cells", width)));
+            }
+            for (x, c) in row.chars().enumerate() {
+                cells.push(match c {
+                    '#' => CellState::WALL,
+                    '.' => CellState::UNEXPLORED,
+                    'S' if (x, y) == start => CellState::START,
+                    'E' if (x, y) == end => CellState::END,
+                    'S' | 'E' => return Err(lines.error(format!("`{}` at {},{} does not match the start and end", c, x, y))),
+                    _ => return Err(lines.error(format!("unknown cell `{}`", c))),
+                });
+            }
+        }
+        let maze = Maze::new(width, height, cells, start, end, seed).map_err(|message| lines.error(message))?;
+        // A maze whose end could not be placed away from the start shows only the end.
+        let start_ok = start == end || maze.try_get(start.0, start.1) == Some(&CellState::START);
+        if !start_ok || maze.try_get(end.0, end.1) != Some(&CellState::END) {
+            return Err(lines.error("the maze must show `S` at the start and `E` at the end".to_string()));
+        }
+        let step_count = lines.field("steps")?.parse::<usize>().map_err(|err| lines.error(err.to_string()))?;
+        let mut steps = vec![];
+        while steps.len() < step_count {
+            let Some(line) = lines.next_line() else {
+                break;
+            };
+            let events = line
+                .split(';')
+                .map(str::trim)
+                .filter(|event| !event.is_empty())
+                .map(|event| parse_event(event).map_err(|message| lines.error(message)))
+                .collect::<Result<Vec<SolverEvent>, TraceError>>()?;
+            steps.push(events);
+        }
+        // Trailing empty steps may have been stripped by an editor. A solver leaves far fewer
+        // than four per cell, a count beyond that is wrong rather than stripped.
+        if step_count - steps.len() > maze.cells.len().saturating_mul(4) {
+            return Err(lines.error(format!("expected {} steps, found {}", step_count, steps.len())));
+        }
+        steps.resize(step_count, vec![]);
+        Ok(Self {
+            maze,
+            algorithm,
+            seed,
+            steps,
+        })
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "algorithm {}", self.algorithm)?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "size {} {}", self.maze.width, self.maze.height)?;
+        writeln!(f, "start {} {}", self.maze.start.0, self.maze.start.1)?;
+        writeln!(f, "end {} {}", self.maze.end.0, self.maze.end.1)?;
+        writeln!(f, "maze")?;
+        for row in self.maze.cells.chunks(self.maze.width) {
+            let row = row.iter().map(|cell| match cell {
+                CellState::WALL => '#',
+                CellState::START => 'S',
+                CellState::END => 'E',
+                _ => '.',
+            }).collect::<String>();
+            writeln!(f, "{}", row)?;
+        }
+        writeln!(f, "steps {}", self.steps.len())?;
+        for events in self.steps.iter() {
+            let line = events.iter().map(|event| match event {
+                SolverEvent::Expanded((x, y)) => format!("expand {},{}", x, y),
+                SolverEvent::FrontierPush((x, y)) => format!("push {},{}", x, y),
+                SolverEvent::PathFound(path) => {
+                    let points = path.iter().map(|(x, y)| format!(" {},{}", x, y)).collect::<String>();
+                    format!("path{}", points)
+                }
+            }).collect::<Vec<String>>();
+            writeln!(f, "{}", line.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+struct Lines<'a> {
+    lines: std::iter::Enumerate<std::str::Lines<'a>>,
+    current: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn error(&self, message: String) -> TraceError {
+        TraceError::Parse { line: self.current, message }
+    }
+
+    fn next(&mut self) -> Result<&'a str, TraceError> {
+        match self.lines.next() {
+            Some((index, line)) => {
+                self.current = index + 1;
+                Ok(line.trim_end())
+            }
+            None => Err(TraceError::Parse { line: self.current + 1, message: "unexpected end of trace".to_string() }),
+        }
+    }
+
+    // `None` at the end of the trace, where `next` is an error.
+    fn next_line(&mut self) -> Option<&'a str> {
+        self.next().ok()
+    }
+
+    fn field(&mut self, name: &str) -> Result<&'a str, TraceError> {
+        let line = self.next()?;
+        match line.split_once(' ') {
+            Some((key, value)) if key == name => Ok(value.trim()),
+            _ => Err(self.error(format!("expected `{} <value>`", name))),
+        }
+    }
+
+    fn point(&mut self, name: &str) -> Result<(usize, usize), TraceError> {
+        let value = self.field(name)?;
+        value
+            .split_once(' ')
+            .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+            .ok_or_else(|| self.error(format!("expected `{} <x> <y>`", name)))
+    }
+}
+
+fn parse_coords(text: &str) -> Result<(usize, usize), String> {
+    text.split_once(',')
+        .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+        .ok_or_else(|| format!("invalid coordinates `{}`", text))
+}
+
+fn parse_event(event: &str) -> Result<SolverEvent, String> {
+    let mut parts = event.split_whitespace();
+    let kind = parts.next().unwrap_or_default();
+    match kind {
+        "expand" => Ok(SolverEvent::Expanded(parse_coords(parts.next().unwrap_or_default())?)),
+        "push" => Ok(SolverEvent::FrontierPush(parse_coords(parts.next().unwrap_or_default())?)),
+        "path" => Ok(SolverEvent::PathFound(parts.map(parse_coords).collect::<Result<Vec<(usize, usize)>, String>>()?)),
+        _ => Err(format!("unknown event `{}`", kind)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{generate_maze, Generator};
+    use crate::path_finders::a_star::AStar;
+    use crate::path_finders::path_finder_interface::PathFinder;
+    use crate::path_finders::solve;
+    use crate::parse_error;
+
+    const EXAMPLE: &str = "mazes-trace 1
+algorithm A*
+seed 1234
+size 5 3
+start 0 0
+end 4 2
+maze
+S.#..
+#.#.#
+....E
+steps 2
+expand 0,0; push 1,0
+expand 1,0; push 1,1
+";
+
+    #[test]
+    fn round_trip() {
+        let maze = generate_maze(12, 9, Generator::Scatter, 7);
+        let mut solver = AStar::new(&maze);
+        let steps = solve(&mut solver, &maze).into_iter().map(|(events, _, _)| events).collect::<Vec<_>>();
+        let trace = Trace::new(&maze, solver.name(), steps.clone());
+        let text = trace.to_string();
+        let parsed = Trace::parse(&text).unwrap();
+        assert_eq!(parsed.algorithm, "A*");
+        assert_eq!(parsed.seed, 7);
+        assert_eq!(parsed.steps, steps);
+        assert_eq!((parsed.maze.width, parsed.maze.height), (12, 9));
+        assert_eq!((parsed.maze.start, parsed.maze.end), (maze.start, maze.end));
+        assert_eq!(parsed.maze.cells, trace.maze.cells);
+        assert_eq!(parsed.to_string(), text);
+    }
+
+    #[test]
+    fn example_parses() {
+        let trace = Trace::parse(EXAMPLE).unwrap();
+        assert_eq!(trace.maze.get(2, 0), &CellState::WALL);
+        assert_eq!(trace.steps[1], vec![SolverEvent::Expanded((1, 0)), SolverEvent::FrontierPush((1, 1))]);
+        assert_eq!(trace.to_string(), EXAMPLE);
+    }
+
+    #[test]
+    fn stripped_trailing_steps_are_empty() {
+        let trace = Trace::parse(&EXAMPLE.replace("steps 2", "steps 5")).unwrap();
+        assert_eq!(trace.steps.len(), 5);
+        assert!(trace.steps[2..].iter().all(|events| events.is_empty()));
+    }
+
+    #[test]
+    fn malformed_traces_are_errors() {
+        assert_eq!(parse_error(Trace::parse, &EXAMPLE.replace("mazes-trace 1", "mazes-trace 2")), "line 1: expected `mazes-trace 1`");
+        assert_eq!(parse_error(Trace::parse, &EXAMPLE.replace("end 4 2", "end 5 2")), "line 6: start and end must lie inside the maze");
+        assert_eq!(parse_error(Trace::parse, &EXAMPLE.replace("S.#..", "S.#.")), "line 8: expected a row of 5 cells");
+        assert_eq!(parse_error(Trace::parse, &EXAMPLE.replace("S.#..", "S.#.x")), "line 8: unknown cell `x`");
+        assert_eq!(parse_error(Trace::parse, &EXAMPLE.replace("S.#..", "..#.S")), "line 8: `S` at 4,0 does not match the start and end");
+        assert_eq!(parse_error(Trace::parse, &EXAMPLE.replace("S.#..", "..#..")), "line 10: the maze must show `S` at the start and `E` at the end");
+        assert_eq!(parse_error(Trace::parse, &EXAMPLE.replace("push 1,1", "push 1")), "line 13: invalid coordinates `1`");
+        assert_eq!(parse_error(Trace::parse, &EXAMPLE[..EXAMPLE.find("maze\n").unwrap()]), "line 7: unexpected end of trace");
+    }
+
+    #[test]
+    fn untrusted_sizes_are_errors() {
+        let huge = EXAMPLE.replace("size 5 3", "size 4294967296 4294967296").replace("end 4 2", "end 4294967295 4294967295");
+        assert_eq!(parse_error(Trace::parse, &huge), "line 6: the maze has too many cells");
+        let many_steps = EXAMPLE.replace("steps 2", &format!("steps {}", usize::MAX));
+        assert_eq!(parse_error(Trace::parse, &many_steps), format!("line 13: expected {} steps, found 2", usize::MAX));
+    }
+}
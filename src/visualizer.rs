@@ -0,0 +1,309 @@
+use bevy::asset::{Assets, Handle};
+use bevy::ecs::entity::Entity;
+use bevy::prelude::{Camera, Commands, Component, DetectChangesMut, GlobalTransform, Or, Query, Res, ResMut, Resource, Sprite, SpriteBundle, Transform, Window, With, Without};
+use bevy::math::Vec2;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::Image;
+use crate::cli::Renderer;
+use crate::maze::{CellState, Maze};
+use crate::plugin::MazeVisualizerConfig;
+
+// Width and height in cells of one texture of the image renderer.
+const CHUNK_SIZE: usize = 256;
+
+#[derive(Component)]
+pub struct Cell{
+    pub position: (usize, usize),
+    pub type_: CellState
+}
+
+
+#[derive(Resource)]
+pub struct CellAssets {
+    pub start_tile: Handle<Image>,
+    pub end_tile: Handle<Image>,
+    pub wall_tile: Handle<Image>,
+    pub unexplored_tile: Handle<Image>,
+    pub explored_tile: Handle<Image>,
+    pub frontier_tile: Handle<Image>,
+    pub path_tile: Handle<Image>,
+
+}
+#[derive(Resource)]
+pub struct CellSize(pub usize);
+
+/// What draws the main maze, picked by `Renderer::for_maze` whenever a maze is rendered.
+#[derive(Resource, Default)]
+pub enum MazeView {
+    #[default]
+    Empty,
+    /// The sprite of every cell in row-major order, so a changed cell is found without searching.
+    Sprites(Vec<Entity>),
+    Image(ChunkedImage),
+}
+
+/// A sprite showing the chunk with this index of the main maze's `ChunkedImage`.
+#[derive(Component)]
+pub struct MazeChunk(pub usize);
+
+/// Query filter for whatever shows the main maze, cell sprites or chunks.
+pub type MainMaze = Or<(With<Cell>, With<MazeChunk>)>;
+
+/// A maze drawn with a pixel per cell, split over square textures so a change only uploads the chunk it is in.
+pub struct ChunkedImage {
+    chunks: Vec<Handle<Image>>,
+    columns: usize,
+    width: usize,
+    height: usize,
+}
+
+impl ChunkedImage {
+    pub fn new(maze: &Maze, images: &mut Assets<Image>) -> Self {
+        let columns = maze.width.div_ceil(CHUNK_SIZE);
+        let rows = maze.height.div_ceil(CHUNK_SIZE);
+        let chunks = (0..rows * columns)
+            .map(|chunk| {
+                let (width, height) = chunk_extent(chunk % columns, chunk / columns, maze.width, maze.height);
+                let size = Extent3d { width: width as u32, height: height as u32, depth_or_array_layers: 1 };
+                let data = vec![0; width * height * 4];
+                images.add(Image::new(size, TextureDimension::D2, data, TextureFormat::Rgba8UnormSrgb, RenderAssetUsages::default()))
+            })
+            .collect();
+        let image = Self { chunks, columns, width: maze.width, height: maze.height };
+        image.paint(images, 0..maze.cells.len(), |index| get_color(&maze.cells[index]));
+        image
+    }
+
+    // The chunk showing cell `index` of the maze and where its pixel starts in the chunk's data.
+    fn pixel(&self, index: usize) -> (usize, usize) {
+        let (x, y) = (index % self.width, index / self.width);
+        let (column, row) = (x / CHUNK_SIZE, y / CHUNK_SIZE);
+        let (width, height) = chunk_extent(column, row, self.width, self.height);
+        // Image rows run top down, maze rows bottom up.
+        let (pixel_x, pixel_y) = (x % CHUNK_SIZE, height - 1 - y % CHUNK_SIZE);
+        (row * self.columns + column, (pixel_y * width + pixel_x) * 4)
+    }
+
+    /// Gives the cells at `indices`, indices into `Maze::cells`, the colour `color` picks for them.
+    pub fn paint(&self, images: &mut Assets<Image>, indices: impl IntoIterator<Item = usize>, color: impl Fn(usize) -> [u8; 4]) {
+        let mut current: Option<(usize, &mut Image)> = None;
+        for index in indices {
+            let (chunk, offset) = self.pixel(index);
+            if current.as_ref().map(|(open, _)| *open) != Some(chunk) {
+                current = images.get_mut(&self.chunks[chunk]).map(|image| (chunk, image));
+            }
+            if let Some((_, image)) = current.as_mut() {
+                image.data[offset..offset + 4].copy_from_slice(&color(index));
+            }
+        }
+    }
+
+    /// A sprite per chunk, laid out so the centre of cell `(0, 0)` is at `origin`.
+    pub fn sprites(&self, origin: Vec2, cell_size: f32) -> Vec<SpriteBundle> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .map(|(chunk, texture)| {
+                let (column, row) = (chunk % self.columns, chunk / self.columns);
+                let (width, height) = chunk_extent(column, row, self.width, self.height);
+                let size = Vec2::new(width as f32, height as f32) * cell_size;
+                let corner = origin - Vec2::splat(cell_size / 2.) + Vec2::new(column as f32, row as f32) * CHUNK_SIZE as f32 * cell_size;
+                let centre = corner + size / 2.;
+                SpriteBundle {
+                    transform: Transform::from_xyz(centre.x, centre.y, 0.),
+                    sprite: Sprite { custom_size: Some(size), ..Default::default() },
+                    texture: texture.clone(),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+}
+
+// Size in cells of the chunk in `column` and `row`, the last ones take what is left over.
+fn chunk_extent(column: usize, row: usize, width: usize, height: usize) -> (usize, usize) {
+    (CHUNK_SIZE.min(width - column * CHUNK_SIZE), CHUNK_SIZE.min(height - row * CHUNK_SIZE))
+}
+
+pub fn get_image(cell: &CellState, assets: &CellAssets) -> Handle<Image> {
+    match cell {
+        CellState::START => assets.start_tile.clone(),
+        CellState::END => assets.end_tile.clone(),
+        CellState::WALL => assets.wall_tile.clone(),
+        CellState::UNEXPLORED => assets.unexplored_tile.clone(),
+        CellState::EXPLORED => assets.explored_tile.clone(),
+        CellState::FRONTIER => assets.frontier_tile.clone(),
+        CellState::PATH => assets.path_tile.clone(),
+    }
+}
+
+/// Colour of a cell in the image renderer, the main colour of its tile.
+pub fn get_color(cell: &CellState) -> [u8; 4] {
+    match cell {
+        CellState::START => [232, 193, 112, 255],
+        CellState::END => [190, 70, 70, 255],
+        CellState::WALL => [78, 100, 116, 255],
+        CellState::UNEXPLORED => [52, 45, 56, 255],
+        CellState::EXPLORED => [90, 90, 112, 255],
+        CellState::FRONTIER => [62, 138, 114, 255],
+        CellState::PATH => [143, 108, 97, 255],
+    }
+}
+
+// A new maze of the same size reuses the sprites already there, only a different size spawns them again.
+#[allow(clippy::too_many_arguments)]
+pub fn render_maze(
+    mut commands: Commands, 
+    mut maze: ResMut<Maze>, 
+    assets: Res<CellAssets>,
+    cell_size: Res<CellSize>,
+    config: Res<MazeVisualizerConfig>,
+    mut view: ResMut<MazeView>,
+    mut images: ResMut<Assets<Image>>,
+    cells: Query<Entity, MainMaze>, 
+    mut query: Query<(&mut Cell, &mut Transform, &mut Sprite, &mut Handle<Image>)>) {
+    
+    // Every cell is drawn from scratch below.
+    maze.bypass_change_detection().take_changes();
+    let size = cell_size.0 as f32;
+    if Renderer::for_maze(config.renderer, maze.cells.len()) == Renderer::Image {
+        for entity in cells.iter() {
+            commands.entity(entity).despawn();
+        }
+        let image = ChunkedImage::new(&maze, &mut images);
+        for (index, sprite) in image.sprites(cell_centre((0, 0), &maze, size), size).into_iter().enumerate() {
+            commands.spawn((sprite, MazeChunk(index)));
+        }
+        *view = MazeView::Image(image);
+        return;
+    }
+    let reusable = cells.iter().len() == maze.cells.len();
+    if let (MazeView::Sprites(entities), true) = (&*view, reusable) {
+        for (index, &entity) in entities.iter().enumerate() {
+            let Ok((mut cell, mut transform, mut sprite, mut texture)) = query.get_mut(entity) else {
+                continue;
+            };
+            let (x, y) = maze.position_of(index);
+            let state = &maze.cells[index];
+            cell.position = (x, y);
+            cell.type_ = state.clone();
+            let centre = cell_centre((x, y), &maze, size);
+            transform.translation.x = centre.x;
+            transform.translation.y = centre.y;
+            sprite.custom_size = Some(Vec2::new(size, size));
+            *texture = get_image(state, &assets);
+        }
+        return;
+    }
+    for entity in cells.iter() {
+        commands.entity(entity).despawn();
+    }
+    let mut entities = vec![];
+    for y in 0..maze.height {
+        for x in 0..maze.width {
+            let cell = maze.get(x, y);
+            let texture = get_image(cell, &assets);
+            entities.push(commands.spawn(get_tile_sprite((x, y), cell_centre((x, y), &maze, size), size, cell, texture)).id());
+        }
+    }
+    *view = MazeView::Sprites(entities);
+}
+
+/// World position of the centre of cell `position`. The maze is centred on the origin, where the camera starts.
+pub fn cell_centre(position: (usize, usize), maze: &Maze, cell_size: f32) -> Vec2 {
+    let from_corner = Vec2::new(position.0 as f32 + 0.5, position.1 as f32 + 0.5);
+    (from_corner - Vec2::new(maze.width as f32, maze.height as f32) / 2.) * cell_size
+}
+
+/// The cell under `position` in world coordinates, the inverse of `cell_centre`.
+pub fn cell_at(position: Vec2, maze: &Maze, cell_size: f32) -> Option<(usize, usize)> {
+    let from_corner = position / cell_size + Vec2::new(maze.width as f32, maze.height as f32) / 2.;
+    let (x, y) = (from_corner.x.floor(), from_corner.y.floor());
+    if x < 0. || y < 0. || x >= maze.width as f32 || y >= maze.height as f32 {
+        return None;
+    }
+    Some((x as usize, y as usize))
+}
+
+/// The cell under the mouse pointer, if it is over the maze.
+pub fn cell_under_cursor(window: &Window, camera: &Camera, camera_transform: &GlobalTransform, maze: &Maze, cell_size: f32) -> Option<(usize, usize)> {
+    let cursor = window.cursor_position()?;
+    let position = camera.viewport_to_world_2d(camera_transform, cursor)?;
+    cell_at(position, maze, cell_size)
+}
+
+fn get_tile_sprite(position: (usize, usize), centre: Vec2, cell_size: f32, cell: &CellState, texture: Handle<Image>) -> (SpriteBundle, Cell){
+        (SpriteBundle {
+            transform: Transform::from_xyz(centre.x, centre.y, 0.0),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(cell_size, cell_size)),
+                ..Default::default()},
+            texture,
+            ..Default::default()
+        },
+        Cell {
+        position,
+        type_: cell.clone()
+        }
+    )
+}
+// Moves the cells to where a new cell size puts them, without drawing them again.
+pub fn fit_maze(
+    maze: Res<Maze>,
+    cell_size: Res<CellSize>,
+    view: Res<MazeView>,
+    mut cells: Query<(&Cell, &mut Transform, &mut Sprite), Without<MazeChunk>>,
+    mut chunks: Query<(&MazeChunk, &mut Transform, &mut Sprite), Without<Cell>>) {
+
+    let size = cell_size.0 as f32;
+    for (cell, mut transform, mut sprite) in cells.iter_mut() {
+        let centre = cell_centre(cell.position, &maze, size);
+        transform.translation.x = centre.x;
+        transform.translation.y = centre.y;
+        sprite.custom_size = Some(Vec2::splat(size));
+    }
+    if let MazeView::Image(image) = &*view {
+        let layout = image.sprites(cell_centre((0, 0), &maze, size), size);
+        for (chunk, mut transform, mut sprite) in chunks.iter_mut() {
+            if let Some(fitted) = layout.get(chunk.0) {
+                transform.translation = fitted.transform.translation;
+                sprite.custom_size = fitted.sprite.custom_size;
+            }
+        }
+    }
+}
+
+// Only the cells the maze reports as changed are drawn again, so a frame costs as much as the solver did.
+pub fn update_maze(
+    mut maze: ResMut<Maze>,
+    view: Res<MazeView>,
+    mut query: Query<(&mut Cell, &mut Handle<Image>)>,
+    mut images: ResMut<Assets<Image>>,
+    assets: Res<CellAssets>) {
+    
+    let changed = maze.bypass_change_detection().take_changes().indices(&maze);
+    if changed.is_empty() {
+        return;
+    }
+    let entities = match &*view {
+        MazeView::Empty => return,
+        MazeView::Sprites(entities) => entities,
+        MazeView::Image(image) => {
+            image.paint(&mut images, changed, |index| get_color(&maze.cells[index]));
+            return;
+        }
+    };
+    for index in changed {
+        let Some(Ok((mut cell, mut texture))) = entities.get(index).map(|&entity| query.get_mut(entity)) else {
+            continue;
+        };
+        let state = &maze.cells[index];
+        if cell.type_ != *state {
+            cell.type_ = state.clone();
+        }
+        *texture = get_image(state, &assets);
+    }
+}
+
+